@@ -2,3 +2,48 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+// reward_per_share in the farm is scaled by this, so small emissions per lp token are not rounded away
+#[constant]
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+// spot prices used to measure volatility for the dynamic fee are scaled by this
+#[constant]
+pub const PRICE_PRECISION: u128 = 1_000_000_000;
+
+// upper bound on intents in one batch, settle_batch takes 4 accounts per intent on top of its own 13,
+// so a legacy transaction only fits about 5 intents and a full batch has to be settled with an address lookup table
+#[constant]
+pub const MAX_BATCH_INTENTS: u8 = 10;
+
+// an intent has to sell at least this share of the pool's reserve of that token, in basis points,
+// so filling a batch up to MAX_BATCH_INTENTS to block trading ties up real liquidity instead of dust
+#[constant]
+pub const MIN_INTENT_SHARE: u64 = 10;
+
+// a batch still not settled this many slots after its settle_slot can not settle, its intents can then be refunded
+#[constant]
+pub const BATCH_REFUND_SLOTS: u64 = 150;
+
+// weighted pools hold between 2 and MAX_WEIGHTED_ASSETS assets
+pub const MAX_WEIGHTED_ASSETS: usize = 8;
+
+// smallest weight an asset of a weighted pool can have, in basis points
+#[constant]
+pub const MIN_WEIGHT: u16 = 100;
+
+// lp minted on the first join of a weighted pool that is locked in the pool for good, so the lp supply can never get back to dust
+#[constant]
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+// a single swap, join or exit on a weighted pool can not move more than this share of an asset's balance, in basis points
+#[constant]
+pub const MAX_RATIO: u64 = 3_000;
+
+// share of a filled limit order's output paid to the keeper that filled it, in basis points
+#[constant]
+pub const KEEPER_TIP: u16 = 10;
+
+// boost_share can redirect at most this much of the swap fees to locked positions, in basis points
+#[constant]
+pub const MAX_BOOST_SHARE: u16 = 5_000;
@@ -43,7 +43,49 @@ pub enum AmmError {
     #[msg("Zero balance.")]
     ZeroBalance,
     #[msg("Swap error.")]
-    SwapError
+    SwapError,
+    #[msg("Reward duration must be greater than zero.")]
+    InvalidDuration,
+    #[msg("No rewards to claim.")]
+    NoRewardsToClaim,
+    #[msg("This pool only accepts swaps through batches.")]
+    BatchModeEnabled,
+    #[msg("Batch mode is not enabled for this pool.")]
+    BatchModeDisabled,
+    #[msg("Invalid batch.")]
+    InvalidBatch,
+    #[msg("Batch is full.")]
+    BatchFull,
+    #[msg("Batch can not be settled yet.")]
+    BatchNotReady,
+    #[msg("Invalid swap intent.")]
+    InvalidIntent,
+    #[msg("All intents of the batch have to be settled together.")]
+    MissingIntents,
+    #[msg("Invalid weights.")]
+    InvalidWeights,
+    #[msg("Invalid number of assets.")]
+    InvalidAssetCount,
+    #[msg("Amount is too large compared to the pool balance.")]
+    MaxRatioExceeded,
+    #[msg("Invalid price.")]
+    InvalidPrice,
+    #[msg("Invalid limit order.")]
+    InvalidOrder,
+    #[msg("None of the orders could be filled.")]
+    NoOrdersFilled,
+    #[msg("Locked liquidity can not be withdrawn before expiry.")]
+    LockNotExpired,
+    #[msg("Locker accounts are required while fees are boosted.")]
+    LockerRequired,
+    #[msg("This pool requires a trader permit.")]
+    PermitRequired,
+    #[msg("Config already has the current layout.")]
+    AlreadyMigrated,
+    #[msg("Intent is too small compared to the pool reserve.")]
+    IntentTooSmall,
+    #[msg("Batch can still be settled.")]
+    BatchNotExpired,
 }
 
 impl From<CurveError> for AmmError {
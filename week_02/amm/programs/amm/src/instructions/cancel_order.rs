@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{error::AmmError, state::{Config, LimitOrder}};
+
+// the owner of a limit order can take back the escrowed tokens at any time before it is filled
+/*
+    accounts used:
+    - user
+    - mint_in
+    - config
+    - order, order_vault
+    - user_ata_in
+    - two programs
+*/
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        constraint = mint_in.key() == if order.is_x { config.mint_x } else { config.mint_y } @ AmmError::InvalidToken
+    )]
+    pub mint_in: Account<'info, Mint>,
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = user,
+        has_one = config,
+        constraint = order.owner == user.key() @ AmmError::InvalidAuthority,
+        seeds = [b"order", config.key().as_ref(), user.key().as_ref(), order.seed.to_le_bytes().as_ref()],
+        bump = order.bump
+    )]
+    pub order: Account<'info, LimitOrder>,
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = order,
+    )]
+    pub order_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = user,
+    )]
+    pub user_ata_in: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> CancelOrder<'info> {
+    pub fn cancel_order(&mut self) -> Result<()> {
+        let config_key = self.config.key();
+        let owner_key = self.user.key();
+        let order_seed = self.order.seed.to_le_bytes();
+        let seeds = &[&b"order"[..], config_key.as_ref(), owner_key.as_ref(), order_seed.as_ref(), &[self.order.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: self.order_vault.to_account_info(),
+            to: self.user_ata_in.to_account_info(),
+            authority: self.order.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, self.order_vault.amount)?;
+
+        let cpi_accounts = CloseAccount {
+            account: self.order_vault.to_account_info(),
+            destination: self.user.to_account_info(),
+            authority: self.order.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)
+    }
+}
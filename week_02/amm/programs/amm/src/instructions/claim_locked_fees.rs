@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{error::AmmError, state::{Config, LockedPosition, Locker}};
+
+// this instruction pays out the boosted fees a locked position earned so far, the lp tokens stay locked
+/*
+    accounts required:
+    - user
+    - mint_x, mint_y
+    - config
+    - locker, position
+    - locker_vault_x, locker_vault_y
+    - user_x, user_y
+    - the three programs
+*/
+#[derive(Accounts)]
+pub struct ClaimLockedFees<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        has_one = config,
+        seeds = [b"locker", config.key().as_ref()],
+        bump = locker.bump
+    )]
+    pub locker: Account<'info, Locker>,
+
+    #[account(
+        mut,
+        has_one = config,
+        constraint = position.owner == user.key() @ AmmError::InvalidAuthority,
+        seeds = [b"locked", config.key().as_ref(), user.key().as_ref(), position.seed.to_le_bytes().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, LockedPosition>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = locker
+    )]
+    pub locker_vault_x: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = locker
+    )]
+    pub locker_vault_y: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user
+    )]
+    pub user_x: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user
+    )]
+    pub user_y: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ClaimLockedFees<'info> {
+    pub fn claim_locked_fees(&mut self) -> Result<()> {
+        let (fees_x, fees_y) = self.position.pending(&self.locker)?;
+        require!(fees_x > 0 || fees_y > 0, AmmError::NoRewardsToClaim);
+
+        self.position.sync_debt(&self.locker)?;
+
+        self.pay_fees(true, fees_x)?;
+        self.pay_fees(false, fees_y)
+    }
+
+    pub fn pay_fees(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let (from, to) = match is_x {
+            true => (self.locker_vault_x.to_account_info(), self.user_x.to_account_info()),
+            false => (self.locker_vault_y.to_account_info(), self.user_y.to_account_info()),
+        };
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.locker.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[&b"locker"[..], config_key.as_ref(), &[self.locker.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)
+    }
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{error::AmmError, state::{Config, Farm, FarmPosition}};
+
+// this instruction pays out everything a position has earned in the farm so far
+/*
+    accounts required:
+    - user
+    - config
+    - reward_mint
+    - farm, position
+    - user_reward_ata, reward_vault
+    - the three programs
+*/
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one = config,
+        has_one = reward_mint,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        mut,
+        has_one = farm,
+        constraint = position.owner == user.key() @ AmmError::InvalidAuthority,
+        seeds = [b"position", farm.key().as_ref(), user.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, FarmPosition>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user
+    )]
+    pub user_reward_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ClaimRewards<'info> {
+    pub fn claim_rewards(&mut self) -> Result<()> {
+        self.farm.update_rewards(Clock::get()?.unix_timestamp)?;
+        self.position.accrue(self.farm.reward_per_share)?;
+        self.position.sync_debt(self.farm.reward_per_share)?;
+
+        let amount = self.position.pending_rewards;
+        require!(amount > 0, AmmError::NoRewardsToClaim);
+
+        let cpi_accounts = Transfer {
+            from: self.reward_vault.to_account_info(),
+            to: self.user_reward_ata.to_account_info(),
+            authority: self.farm.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[&b"farm"[..], config_key.as_ref(), &[self.farm.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+
+        self.position.pending_rewards = 0;
+        Ok(())
+    }
+}
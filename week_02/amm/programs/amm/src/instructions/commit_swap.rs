@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{error::AmmError, state::{Batch, Config, SwapIntent, TraderPermit}, MAX_BATCH_INTENTS, MIN_INTENT_SHARE};
+
+// in batch mode this replaces swap: the tokens being sold are escrowed in the batch and swapped when the batch settles
+/*
+    accounts used:
+    - user
+    - mint_x, mint_y
+    - config
+    - vault_x, vault_y
+    - batch, intent
+    - batch_vault_x, batch_vault_y
+    - user_x, user_y
+    - permit (only needed while config.permissioned is set)
+    - three programs
+*/
+#[derive(Accounts)]
+#[instruction(batch_id: u64)]
+pub struct CommitSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>, // only read, the intent size is checked against the reserves
+    #[account(
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Batch::INIT_SPACE,
+        seeds = [b"batch", config.key().as_ref(), batch_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub batch: Account<'info, Batch>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + SwapIntent::INIT_SPACE,
+        seeds = [b"intent", batch.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub intent: Account<'info, SwapIntent>, // one intent per user and batch
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = batch,
+    )]
+    pub batch_vault_x: Account<'info, TokenAccount>, // x escrowed by the intents of this batch
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = batch,
+    )]
+    pub batch_vault_y: Account<'info, TokenAccount>, // y escrowed by the intents of this batch
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    pub user_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    pub user_y: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [b"permit", config.key().as_ref(), user.key().as_ref()],
+        bump = permit.bump
+    )]
+    pub permit: Option<Account<'info, TraderPermit>>, // only needed while config.permissioned is set
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CommitSwap<'info> {
+    // amount, is_x and min mean the same as in swap, min is checked against the uniform price at settlement
+    pub fn commit_swap(&mut self, batch_id: u64, amount: u64, is_x: bool, min: u64, bumps: &CommitSwapBumps) -> Result<()> {
+        require!(!self.config.locked, AmmError::PoolLocked);
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(!self.config.permissioned || self.permit.is_some(), AmmError::PermitRequired);
+
+        // intents can only be committed to the batch of the current window
+        let batch_slots = self.config.batch_slots.ok_or(AmmError::BatchModeDisabled)?;
+        require!(Clock::get()?.slot / batch_slots == batch_id, AmmError::InvalidBatch);
+
+        // first intent of this window opens the batch
+        if self.batch.config == Pubkey::default() {
+            self.batch.set_inner(Batch {
+                config: self.config.key(),
+                batch_id,
+                settle_slot: batch_id
+                    .checked_add(1)
+                    .and_then(|next| next.checked_mul(batch_slots))
+                    .ok_or(AmmError::Overflow)?,
+                payer: self.user.key(),
+                total_x_in: 0,
+                total_y_in: 0,
+                intent_count: 0,
+                bump: bumps.batch,
+            });
+        }
+
+        require!(self.batch.intent_count < MAX_BATCH_INTENTS, AmmError::BatchFull);
+
+        let reserve = match is_x {
+            true => self.vault_x.amount,
+            false => self.vault_y.amount,
+        };
+        require!(reserve > 0, AmmError::NoLiquidityInPool);
+        require!(
+            amount as u128 * 10_000 >= reserve as u128 * MIN_INTENT_SHARE as u128,
+            AmmError::IntentTooSmall
+        );
+
+        match is_x {
+            true => self.batch.total_x_in = self.batch.total_x_in.checked_add(amount).ok_or(AmmError::Overflow)?,
+            false => self.batch.total_y_in = self.batch.total_y_in.checked_add(amount).ok_or(AmmError::Overflow)?,
+        }
+        self.batch.intent_count += 1;
+
+        self.intent.set_inner(SwapIntent {
+            owner: self.user.key(),
+            batch: self.batch.key(),
+            is_x,
+            amount,
+            min,
+            bump: bumps.intent,
+        });
+
+        self.escrow_tokens_being_sold(is_x, amount)
+    }
+
+    pub fn escrow_tokens_being_sold(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.user_x.to_account_info(), self.batch_vault_x.to_account_info()),
+            false => (self.user_y.to_account_info(), self.batch_vault_y.to_account_info()),
+        };
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, amount)
+    }
+}
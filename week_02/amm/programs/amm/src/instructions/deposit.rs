@@ -2,7 +2,7 @@ use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token::{accessor::mint, mint_to, transfer, Mint, MintTo, Token, TokenAccount, Transfer}};
 use constant_product_curve::ConstantProduct;
 
-use crate::{state::Config};
+use crate::{state::{Config, TraderPermit}};
 use crate::{error::AmmError};
 
 // this is helpful for liquidity providers in order to deposit their tokens
@@ -14,10 +14,10 @@ use crate::{error::AmmError};
     - config
     - vault_x, vault_y
     - user_x, user_y, user_lp
+    - permit (only needed while config.permissioned is set)
     - the three accounts
 */
 #[derive(Accounts)]
-#[instruction(seed:u64)]
 pub struct Deposit<'info>{
 
     #[account(mut)]
@@ -35,7 +35,7 @@ pub struct Deposit<'info>{
     #[account(
         has_one = mint_x,
         has_one = mint_y,
-        seeds = [b"config", seed.to_le_bytes().as_ref()],
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
     pub config: Account<'info,Config>,
@@ -76,6 +76,12 @@ pub struct Deposit<'info>{
     )]
     pub user_lp: Account<'info,TokenAccount>,
 
+    #[account(
+        seeds = [b"permit", config.key().as_ref(), user.key().as_ref()],
+        bump = permit.bump
+    )]
+    pub permit: Option<Account<'info, TraderPermit>>, // only needed while config.permissioned is set
+
     pub system_program: Program<'info,System>,
     pub token_program: Program<'info,Token>,
     pub associated_token_program: Program<'info,AssociatedToken>,
@@ -91,6 +97,7 @@ impl <'info> Deposit<'info> {
         // if required condition is not true, then returns the mentioned error
         require!(self.config.locked == false, AmmError::PoolLocked);
         require!(amount > 0, AmmError::InvalidAmount);
+        require!(!self.config.permissioned || self.permit.is_some(), AmmError::PermitRequired);
 
         let (x, y) = match self.mint_lp.supply == 0
             && self.vault_x.amount == 0
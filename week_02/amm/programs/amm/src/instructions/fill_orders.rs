@@ -0,0 +1,293 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+use constant_product_curve::{ConstantProduct, LiquidityPair};
+
+use crate::{error::AmmError, state::{Config, DynamicFee, LimitOrder, Locker}, KEEPER_TIP, PRICE_PRECISION};
+
+// permissionless crank that fills every passed limit order whose limit the pool's price has crossed, the keeper earns a tip per filled order
+/*
+    accounts used:
+    - keeper
+    - mint_x, mint_y, mint_lp
+    - config
+    - vault_x, vault_y
+    - keeper_x, keeper_y
+    - locker, locker_vault_x, locker_vault_y (only needed while config.boost_share is set)
+    - three programs
+    - remaining accounts: for every order [order, order_vault, owner, owner_ata_out]
+*/
+#[derive(Accounts)]
+pub struct FillOrders<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Account<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        associated_token::mint = mint_x,
+        associated_token::authority = keeper,
+    )]
+    pub keeper_x: Account<'info, TokenAccount>, // tips of orders selling y are paid in x
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        associated_token::mint = mint_y,
+        associated_token::authority = keeper,
+    )]
+    pub keeper_y: Account<'info, TokenAccount>, // tips of orders selling x are paid in y
+    #[account(
+        mut,
+        seeds = [b"locker", config.key().as_ref()],
+        bump = locker.bump
+    )]
+    pub locker: Option<Account<'info, Locker>>, // receives the boosted part of the fee for locked lp positions
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = locker,
+    )]
+    pub locker_vault_x: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = locker,
+    )]
+    pub locker_vault_y: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> FillOrders<'info> {
+    // orders that can not be filled yet are skipped, so a keeper can pass every order it tracks
+    pub fn fill_orders(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(!self.config.locked, AmmError::PoolLocked);
+
+        let orders = remaining_accounts.chunks_exact(4);
+        require!(!remaining_accounts.is_empty() && orders.remainder().is_empty(), AmmError::InvalidOrder);
+
+        let fee = self.config.swap_fee(Clock::get()?.unix_timestamp);
+        let (mut x, mut y) = (self.vault_x.amount, self.vault_y.amount);
+        let mut filled: Vec<Pubkey> = Vec::new();
+
+        for accounts in orders {
+            let order: Account<'info, LimitOrder> = Account::try_from(&accounts[0])?;
+            let (mint_in, mint_out) = match order.is_x {
+                true => (self.mint_x.key(), self.mint_y.key()),
+                false => (self.mint_y.key(), self.mint_x.key()),
+            };
+
+            require!(order.config == self.config.key(), AmmError::InvalidOrder);
+            require!(!filled.contains(&order.key()), AmmError::InvalidOrder);
+            require!(accounts[1].key() == get_associated_token_address(&order.key(), &mint_in), AmmError::InvalidOrder);
+            require!(accounts[2].key() == order.owner, AmmError::InvalidOrder);
+            require!(accounts[3].key() == get_associated_token_address(&order.owner, &mint_out), AmmError::InvalidOrder);
+
+            let order_vault: Account<'info, TokenAccount> = Account::try_from(&accounts[1])?;
+
+            let Some((amount_out, tip)) = self.quote(&order, order_vault.amount, x, y, fee)? else {
+                continue;
+            };
+
+            // like in swap, part of the fee can go to the locked lp positions instead of staying in the pool
+            let boost = self.config.boost_fee(self.locker.as_deref_mut(), order.is_x, order_vault.amount, fee)?;
+            let deposit = order_vault.amount - boost;
+
+            self.execute(&order, &accounts[1], &accounts[3], deposit, amount_out, tip)?;
+            self.pay_boosted_fee(&order, &accounts[1], boost)?;
+
+            let paid = amount_out.checked_add(tip).ok_or(AmmError::Overflow)?;
+            let (x_after, y_after) = match order.is_x {
+                true => (
+                    x.checked_add(deposit).ok_or(AmmError::Overflow)?,
+                    y.checked_sub(paid).ok_or(AmmError::Underflow)?,
+                ),
+                false => (
+                    x.checked_sub(paid).ok_or(AmmError::Underflow)?,
+                    y.checked_add(deposit).ok_or(AmmError::Overflow)?,
+                ),
+            };
+
+            if let Some(dynamic_fee) = self.config.dynamic_fee.as_mut() {
+                dynamic_fee.record(DynamicFee::spot_price(x, y)?, DynamicFee::spot_price(x_after, y_after)?)?;
+            }
+            (x, y) = (x_after, y_after);
+
+            // the owner gets the rent of the order and its vault back
+            self.close_order_vault(&order, &accounts[1], &accounts[2])?;
+            order.close(accounts[2].clone())?;
+            filled.push(order.key());
+        }
+
+        require!(!filled.is_empty(), AmmError::NoOrdersFilled);
+        Ok(())
+    }
+
+    // returns what the owner and the keeper receive, or None when the pool's price does not satisfy the limit yet
+    pub fn quote(&self, order: &LimitOrder, amount: u64, x: u64, y: u64, fee: u16) -> Result<Option<(u64, u64)>> {
+        if amount == 0 || x == 0 || y == 0 {
+            return Ok(None);
+        }
+
+        let spot_price = DynamicFee::spot_price(x, y)?;
+        let limit_price = order.limit_price as u128;
+
+        // cheap check first: the spot price has to have crossed the limit
+        let crossed = match order.is_x {
+            true => spot_price >= limit_price,
+            false => spot_price <= limit_price,
+        };
+        if !crossed {
+            return Ok(None);
+        }
+
+        let mut curve = ConstantProduct::init(x, y, self.mint_lp.supply, fee, None)
+            .map_err(|_| AmmError::CurveError)?;
+
+        let pair = match order.is_x {
+            true => LiquidityPair::X,
+            false => LiquidityPair::Y,
+        };
+
+        let Ok(res) = curve.swap(pair, amount, 0) else {
+            return Ok(None);
+        };
+
+        let tip = (res.withdraw as u128 * KEEPER_TIP as u128).div_ceil(10_000) as u64;
+        let amount_out = res.withdraw.saturating_sub(tip);
+
+        // after the curve's slippage, the fee and the tip the owner still has to get the limit price or better
+        let satisfied = match order.is_x {
+            true => amount_out as u128 * PRICE_PRECISION >= amount as u128 * limit_price,
+            false => amount_out as u128 * limit_price >= amount as u128 * PRICE_PRECISION,
+        };
+
+        match satisfied && amount_out > 0 {
+            true => Ok(Some((amount_out, tip))),
+            false => Ok(None),
+        }
+    }
+
+    pub fn execute(
+        &self,
+        order: &Account<'info, LimitOrder>,
+        order_vault: &AccountInfo<'info>,
+        owner_ata_out: &AccountInfo<'info>,
+        amount: u64,
+        amount_out: u64,
+        tip: u64,
+    ) -> Result<()> {
+        let (vault_in, vault_out, keeper_out) = match order.is_x {
+            true => (self.vault_x.to_account_info(), self.vault_y.to_account_info(), self.keeper_y.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.vault_x.to_account_info(), self.keeper_x.to_account_info()),
+        };
+
+        // escrowed tokens go from the order vault into the pool
+        let config_key = self.config.key();
+        let order_seed = order.seed.to_le_bytes();
+        let seeds = &[&b"order"[..], config_key.as_ref(), order.owner.as_ref(), order_seed.as_ref(), &[order.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: order_vault.clone(),
+            to: vault_in,
+            authority: order.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+
+        // tokens being bought go from the pool to the owner, and the tip to the keeper
+        let seeds = &[&b"config"[..], &self.config.seed.to_le_bytes(), &[self.config.config_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: vault_out.clone(),
+            to: owner_ata_out.clone(),
+            authority: self.config.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount_out)?;
+
+        if tip == 0 {
+            return Ok(());
+        }
+
+        let cpi_accounts = Transfer {
+            from: vault_out,
+            to: keeper_out,
+            authority: self.config.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, tip)
+    }
+
+    // the boosted part of the fee goes from the order vault straight to the locker
+    pub fn pay_boosted_fee(&self, order: &Account<'info, LimitOrder>, order_vault: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let locker_vault = match order.is_x {
+            true => self.locker_vault_x.as_ref(),
+            false => self.locker_vault_y.as_ref(),
+        };
+
+        let config_key = self.config.key();
+        let order_seed = order.seed.to_le_bytes();
+        let seeds = &[&b"order"[..], config_key.as_ref(), order.owner.as_ref(), order_seed.as_ref(), &[order.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: order_vault.clone(),
+            to: locker_vault.ok_or(AmmError::LockerRequired)?.to_account_info(),
+            authority: order.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)
+    }
+
+    pub fn close_order_vault(&self, order: &Account<'info, LimitOrder>, order_vault: &AccountInfo<'info>, owner: &AccountInfo<'info>) -> Result<()> {
+        let config_key = self.config.key();
+        let order_seed = order.seed.to_le_bytes();
+        let seeds = &[&b"order"[..], config_key.as_ref(), order.owner.as_ref(), order_seed.as_ref(), &[order.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = CloseAccount {
+            account: order_vault.clone(),
+            destination: owner.clone(),
+            authority: order.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)
+    }
+}
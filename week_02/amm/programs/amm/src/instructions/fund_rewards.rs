@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{error::AmmError, state::{Config, Farm}};
+
+// this instruction lets the pool authority top up the farm and set how long the rewards are emitted for
+/*
+    accounts required:
+    - authority
+    - config
+    - reward_mint
+    - farm
+    - authority_reward_ata, reward_vault
+    - the two programs
+*/
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one = config,
+        has_one = reward_mint,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = authority
+    )]
+    pub authority_reward_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> FundRewards<'info> {
+    // amount is added on top of whatever is still left to emit, and the sum is spread evenly over duration seconds
+    pub fn fund_rewards(&mut self, amount: u64, duration: i64) -> Result<()> {
+        require!(self.config.authority == Some(self.authority.key()), AmmError::InvalidAuthority);
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(duration > 0, AmmError::InvalidDuration);
+
+        let now = Clock::get()?.unix_timestamp;
+        self.farm.update_rewards(now)?;
+
+        let leftover = match now < self.farm.period_end {
+            true => ((self.farm.period_end - now) as u128)
+                .checked_mul(self.farm.reward_rate as u128)
+                .ok_or(AmmError::Overflow)?,
+            false => 0,
+        };
+
+        let total = leftover
+            .checked_add(amount as u128)
+            .ok_or(AmmError::Overflow)?;
+
+        let reward_rate = u64::try_from(total / duration as u128).map_err(|_| AmmError::Overflow)?;
+        require!(reward_rate > 0, AmmError::InvalidAmount);
+
+        self.farm.reward_rate = reward_rate;
+        self.farm.last_update = now;
+        self.farm.period_end = now.checked_add(duration).ok_or(AmmError::Overflow)?;
+
+        let cpi_accounts = Transfer {
+            from: self.authority_reward_ata.to_account_info(),
+            to: self.reward_vault.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, amount)
+    }
+}
@@ -77,7 +77,11 @@ impl <'info> Initialize<'info> {
             fee, 
             locked: false, 
             config_bump: bumps.config, 
-            lp_bump: bumps.mint_lp 
+            lp_bump: bumps.mint_lp,
+            dynamic_fee: None, 
+            boost_share: 0, 
+            batch_slots: None, 
+            permissioned: false,
         });
 
         Ok(())
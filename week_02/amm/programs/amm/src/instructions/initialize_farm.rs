@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
+
+use crate::{error::AmmError, state::{Config, Farm}};
+
+// this instruction lets the pool authority attach a rewards farm to a pool
+/*
+    accounts required:
+    - authority
+    - config
+    - mint_lp, reward_mint
+    - farm
+    - farm_lp_vault, reward_vault
+    - the three programs
+*/
+#[derive(Accounts)]
+pub struct InitializeFarm<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    pub reward_mint: Account<'info, Mint>, // rewards can be paid in any mint, not only x or y
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Farm::INIT_SPACE,
+        seeds = [b"farm", config.key().as_ref()],
+        bump
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm
+    )]
+    pub farm_lp_vault: Account<'info, TokenAccount>, // holds the staked lp tokens
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm
+    )]
+    pub reward_vault: Account<'info, TokenAccount>, // holds the rewards that are yet to be claimed
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> InitializeFarm<'info> {
+    pub fn init_farm(&mut self, bumps: &InitializeFarmBumps) -> Result<()> {
+        require!(self.config.authority.is_some(), AmmError::NoAuthoritySet);
+        require!(self.config.authority == Some(self.authority.key()), AmmError::InvalidAuthority);
+
+        let now = Clock::get()?.unix_timestamp;
+
+        // nothing is emitted until the authority calls fund_rewards
+        self.farm.set_inner(Farm {
+            config: self.config.key(),
+            reward_mint: self.reward_mint.key(),
+            reward_per_share: 0,
+            reward_rate: 0,
+            last_update: now,
+            period_end: now,
+            total_staked: 0,
+            bump: bumps.farm,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
+
+use crate::{error::AmmError, state::{Config, Locker}};
+
+// this instruction lets the pool authority set up the locker that holds time-locked lp positions and their boosted fees
+/*
+    accounts required:
+    - authority
+    - mint_x, mint_y, mint_lp
+    - config
+    - locker
+    - locker_lp_vault, locker_vault_x, locker_vault_y
+    - the three programs
+*/
+#[derive(Accounts)]
+pub struct InitializeLocker<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Locker::INIT_SPACE,
+        seeds = [b"locker", config.key().as_ref()],
+        bump
+    )]
+    pub locker: Account<'info, Locker>,
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint_lp,
+        associated_token::authority = locker
+    )]
+    pub locker_lp_vault: Account<'info, TokenAccount>, // holds the locked lp tokens
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint_x,
+        associated_token::authority = locker
+    )]
+    pub locker_vault_x: Account<'info, TokenAccount>, // boosted fees paid in x
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint_y,
+        associated_token::authority = locker
+    )]
+    pub locker_vault_y: Account<'info, TokenAccount>, // boosted fees paid in y
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> InitializeLocker<'info> {
+    pub fn init_locker(&mut self, bumps: &InitializeLockerBumps) -> Result<()> {
+        require!(self.config.authority == Some(self.authority.key()), AmmError::InvalidAuthority);
+
+        self.locker.set_inner(Locker {
+            config: self.config.key(),
+            total_locked: 0,
+            fee_per_share_x: 0,
+            fee_per_share_y: 0,
+            bump: bumps.locker,
+        });
+
+        Ok(())
+    }
+}
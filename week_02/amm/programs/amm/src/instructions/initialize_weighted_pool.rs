@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{create, get_associated_token_address, AssociatedToken, Create},
+    token::{Mint, Token},
+};
+
+use crate::{error::AmmError, state::WeightedPool, MAX_WEIGHTED_ASSETS, MIN_WEIGHT};
+
+// this instruction creates a weighted pool with 2 to 8 assets, the equivalent of initialize for Config
+/*
+    Account context data structure:
+    - initializer
+    - pool
+    - mint_lp
+    - the three accounts
+    - remaining accounts: for every asset [mint, vault], the vault being the ata of the pool
+*/
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct InitializeWeightedPool<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = initializer,
+        space = 8 + WeightedPool::INIT_SPACE,
+        seeds = [b"weighted", seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"lp", pool.key().as_ref()],
+        bump,
+        mint::decimals = 6,
+        mint::authority = pool,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> InitializeWeightedPool<'info> {
+    pub fn init(
+        &mut self,
+        seed: u64,
+        fee: u16,
+        weights: Vec<u16>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        bumps: &InitializeWeightedPoolBumps,
+    ) -> Result<()> {
+        require!(fee < 10_000, AmmError::InvalidFee);
+        require!(weights.len() >= 2 && weights.len() <= MAX_WEIGHTED_ASSETS, AmmError::InvalidAssetCount);
+        require!(remaining_accounts.len() == weights.len() * 2, AmmError::InvalidAssetCount);
+        require!(weights.iter().all(|weight| *weight >= MIN_WEIGHT), AmmError::InvalidWeights);
+        require!(weights.iter().map(|weight| *weight as u32).sum::<u32>() == 10_000, AmmError::InvalidWeights);
+
+        let mut mints: Vec<Pubkey> = Vec::with_capacity(weights.len());
+
+        for accounts in remaining_accounts.chunks(2) {
+            // makes sure the account really is a mint of the token program
+            let mint: Account<'info, Mint> = Account::try_from(&accounts[0])?;
+
+            require!(!mints.contains(&mint.key()), AmmError::InvalidToken);
+            require!(
+                accounts[1].key() == get_associated_token_address(&self.pool.key(), &mint.key()),
+                AmmError::InvalidToken
+            );
+
+            self.create_vault(&accounts[0], &accounts[1])?;
+            mints.push(mint.key());
+        }
+
+        self.pool.set_inner(WeightedPool {
+            seed,
+            mints,
+            weights,
+            fee,
+            pool_bump: bumps.pool,
+            lp_bump: bumps.mint_lp,
+        });
+
+        Ok(())
+    }
+
+    pub fn create_vault(&self, mint: &AccountInfo<'info>, vault: &AccountInfo<'info>) -> Result<()> {
+        let cpi_accounts = Create {
+            payer: self.initializer.to_account_info(),
+            associated_token: vault.clone(),
+            authority: self.pool.to_account_info(),
+            mint: mint.clone(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.associated_token_program.to_account_info(), cpi_accounts);
+        create(cpi_ctx)
+    }
+}
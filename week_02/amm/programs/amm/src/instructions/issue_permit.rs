@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, state::{Config, TraderPermit}};
+
+// the pool authority allows a trader to swap and deposit on a permissioned pool
+/*
+    accounts required:
+    - authority
+    - trader
+    - config
+    - permit
+    - system program
+*/
+#[derive(Accounts)]
+pub struct IssuePermit<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: only used as a seed, any wallet can be allowlisted
+    pub trader: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TraderPermit::INIT_SPACE,
+        seeds = [b"permit", config.key().as_ref(), trader.key().as_ref()],
+        bump
+    )]
+    pub permit: Account<'info, TraderPermit>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> IssuePermit<'info> {
+    pub fn issue_permit(&mut self, bumps: &IssuePermitBumps) -> Result<()> {
+        require!(self.config.authority == Some(self.authority.key()), AmmError::InvalidAuthority);
+
+        self.permit.set_inner(TraderPermit {
+            config: self.config.key(),
+            trader: self.trader.key(),
+            bump: bumps.permit,
+        });
+
+        Ok(())
+    }
+}
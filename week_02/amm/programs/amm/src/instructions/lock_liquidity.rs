@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{error::AmmError, state::{Config, LockedPosition, Locker}};
+
+// this instruction locks lp tokens for a fixed term, in exchange the position earns the boosted share of the swap fees
+/*
+    accounts required:
+    - user
+    - config
+    - mint_lp
+    - locker, position
+    - user_lp, locker_lp_vault
+    - the three programs
+*/
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct LockLiquidity<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"locker", config.key().as_ref()],
+        bump = locker.bump
+    )]
+    pub locker: Account<'info, Locker>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + LockedPosition::INIT_SPACE,
+        seeds = [b"locked", config.key().as_ref(), user.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, LockedPosition>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user
+    )]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = locker
+    )]
+    pub locker_lp_vault: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> LockLiquidity<'info> {
+    // duration is in seconds, the position can only be unlocked once it is over
+    pub fn lock_liquidity(&mut self, seed: u64, amount: u64, duration: i64, bumps: &LockLiquidityBumps) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(duration > 0, AmmError::InvalidDuration);
+        require!(self.user_lp.amount >= amount, AmmError::InsufficientBalance);
+
+        let unlock_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(duration)
+            .ok_or(AmmError::Overflow)?;
+
+        self.position.set_inner(LockedPosition {
+            owner: self.user.key(),
+            config: self.config.key(),
+            seed,
+            amount,
+            unlock_at,
+            debt_x: 0,
+            debt_y: 0,
+            bump: bumps.position,
+        });
+        // fees boosted before the lock do not belong to the new position
+        self.position.sync_debt(&self.locker)?;
+
+        self.locker.total_locked = self.locker.total_locked.checked_add(amount).ok_or(AmmError::Overflow)?;
+
+        let cpi_accounts = Transfer {
+            from: self.user_lp.to_account_info(),
+            to: self.locker_lp_vault.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, amount)
+    }
+}
@@ -0,0 +1,54 @@
+use anchor_lang::{prelude::*, system_program::{transfer, Transfer}, Discriminator};
+
+use crate::{error::AmmError, Config};
+
+// this instruction grows a config created before the dynamic fee, fee boost, batch mode and permits existed to the current size
+/*
+    accounts required:
+    - payer
+    - config
+    - system_program
+*/
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MigrateConfig<'info> {
+    // pays the rent of the new bytes, anyone can, since the new fields start out switched off
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: an old config is too short to deserialize as a Config, owner and discriminator are checked in migrate_config
+    #[account(
+        mut,
+        seeds = [b"config", seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateConfig<'info> {
+    pub fn migrate_config(&mut self, _seed: u64) -> Result<()> {
+        let config = self.config.to_account_info();
+        let space = 8 + Config::INIT_SPACE;
+
+        require_keys_eq!(*config.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+        require!(config.try_borrow_data()?.starts_with(Config::DISCRIMINATOR), ErrorCode::AccountDiscriminatorMismatch);
+        require!(config.data_len() < space, AmmError::AlreadyMigrated);
+
+        let missing_rent = Rent::get()?.minimum_balance(space).saturating_sub(config.lamports());
+        if missing_rent > 0 {
+            let transfer_accounts = Transfer {
+                from: self.payer.to_account_info(),
+                to: config.clone(),
+            };
+
+            transfer(CpiContext::new(self.system_program.to_account_info(), transfer_accounts), missing_rent)?;
+        }
+
+        // the old fields keep their place, the appended bytes are zeroed
+        config.resize(space)?;
+
+        Ok(())
+    }
+}
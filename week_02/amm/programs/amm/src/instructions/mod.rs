@@ -11,4 +11,67 @@ pub mod withdraw;
 pub use withdraw::*;
 
 pub mod update;
-pub use update::*;
\ No newline at end of file
+pub use update::*;
+
+pub mod initialize_farm;
+pub use initialize_farm::*;
+
+pub mod fund_rewards;
+pub use fund_rewards::*;
+
+pub mod stake_lp;
+pub use stake_lp::*;
+
+pub mod unstake_lp;
+pub use unstake_lp::*;
+
+pub mod claim_rewards;
+pub use claim_rewards::*;
+
+pub mod commit_swap;
+pub use commit_swap::*;
+
+pub mod settle_batch;
+pub use settle_batch::*;
+
+pub mod refund_intent;
+pub use refund_intent::*;
+
+pub mod initialize_weighted_pool;
+pub use initialize_weighted_pool::*;
+
+pub mod weighted_liquidity;
+pub use weighted_liquidity::*;
+
+pub mod weighted_swap;
+pub use weighted_swap::*;
+
+pub mod place_order;
+pub use place_order::*;
+
+pub mod fill_orders;
+pub use fill_orders::*;
+
+pub mod cancel_order;
+pub use cancel_order::*;
+
+pub mod initialize_locker;
+pub use initialize_locker::*;
+
+pub mod lock_liquidity;
+pub use lock_liquidity::*;
+
+pub mod claim_locked_fees;
+pub use claim_locked_fees::*;
+
+pub mod unlock_liquidity;
+pub use unlock_liquidity::*;
+
+pub mod issue_permit;
+pub use issue_permit::*;
+
+pub mod revoke_permit;
+pub use revoke_permit::*;
+
+pub mod migrate_config;
+pub use migrate_config::*;
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{error::AmmError, state::{Config, LimitOrder, TraderPermit}};
+
+// this instruction places a limit order on a pool, the tokens being sold are escrowed until a keeper fills the order
+/*
+    accounts used:
+    - user
+    - mint_in
+    - config
+    - order, order_vault
+    - user_ata_in
+    - permit (only needed while config.permissioned is set)
+    - three programs
+*/
+#[derive(Accounts)]
+#[instruction(seed: u64, is_x: bool)]
+pub struct PlaceOrder<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        constraint = mint_in.key() == if is_x { config.mint_x } else { config.mint_y } @ AmmError::InvalidToken
+    )]
+    pub mint_in: Account<'info, Mint>, // mint being sold
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + LimitOrder::INIT_SPACE,
+        seeds = [b"order", config.key().as_ref(), user.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order: Account<'info, LimitOrder>,
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = mint_in,
+        associated_token::authority = order,
+    )]
+    pub order_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = user,
+    )]
+    pub user_ata_in: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [b"permit", config.key().as_ref(), user.key().as_ref()],
+        bump = permit.bump
+    )]
+    pub permit: Option<Account<'info, TraderPermit>>, // only needed while config.permissioned is set
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PlaceOrder<'info> {
+    pub fn place_order(&mut self, seed: u64, is_x: bool, amount: u64, limit_price: u64, bumps: &PlaceOrderBumps) -> Result<()> {
+        require!(!self.config.locked, AmmError::PoolLocked);
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(!self.config.permissioned || self.permit.is_some(), AmmError::PermitRequired);
+        require!(limit_price > 0, AmmError::InvalidPrice);
+
+        self.order.set_inner(LimitOrder {
+            owner: self.user.key(),
+            config: self.config.key(),
+            seed,
+            is_x,
+            amount,
+            limit_price,
+            bump: bumps.order,
+        });
+
+        let cpi_accounts = Transfer {
+            from: self.user_ata_in.to_account_info(),
+            to: self.order_vault.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, amount)
+    }
+}
@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer};
+
+use crate::{error::AmmError, state::{Batch, Config, SwapIntent}, BATCH_REFUND_SLOTS};
+
+// permissionless crank that gives an intent back its escrowed tokens once its batch can no longer be settled
+/*
+    accounts used:
+    - refunder
+    - owner, payer
+    - mint_x, mint_y
+    - config
+    - vault_x, vault_y
+    - batch, intent
+    - batch_vault_x, batch_vault_y
+    - owner_x, owner_y
+    - two programs
+*/
+#[derive(Accounts)]
+pub struct RefundIntent<'info> {
+    pub refunder: Signer<'info>,
+    /// CHECK: receives the refund and the rent of the intent, checked against intent.owner
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+    /// CHECK: receives the rent of the batch once its last intent is refunded, checked against batch.payer
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = payer,
+        seeds = [b"batch", config.key().as_ref(), batch.batch_id.to_le_bytes().as_ref()],
+        bump = batch.bump
+    )]
+    pub batch: Account<'info, Batch>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = batch,
+        seeds = [b"intent", batch.key().as_ref(), owner.key().as_ref()],
+        bump = intent.bump
+    )]
+    pub intent: Account<'info, SwapIntent>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = batch,
+    )]
+    pub batch_vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = batch,
+    )]
+    pub batch_vault_y: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+    )]
+    pub owner_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+    )]
+    pub owner_y: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundIntent<'info> {
+    // the rest of the batch can still be settled afterwards, it just clears without this intent
+    pub fn refund_intent(&mut self) -> Result<()> {
+        let expiry_slot = self.batch.settle_slot.checked_add(BATCH_REFUND_SLOTS).ok_or(AmmError::Overflow)?;
+        require!(Clock::get()?.slot >= expiry_slot, AmmError::BatchNotExpired);
+
+        let (is_x, amount) = (self.intent.is_x, self.intent.amount);
+        match is_x {
+            true => self.batch.total_x_in = self.batch.total_x_in.checked_sub(amount).ok_or(AmmError::Underflow)?,
+            false => self.batch.total_y_in = self.batch.total_y_in.checked_sub(amount).ok_or(AmmError::Underflow)?,
+        }
+        self.batch.intent_count -= 1;
+
+        let (from, to) = match is_x {
+            true => (self.batch_vault_x.to_account_info(), self.owner_x.to_account_info()),
+            false => (self.batch_vault_y.to_account_info(), self.owner_y.to_account_info()),
+        };
+        self.batch_transfer(from, to, amount)?;
+
+        // the last refund closes the batch, anything else sent to its vaults goes to the pool like in settle_batch
+        if self.batch.intent_count == 0 {
+            self.batch_vault_x.reload()?;
+            self.batch_vault_y.reload()?;
+            self.batch_transfer(self.batch_vault_x.to_account_info(), self.vault_x.to_account_info(), self.batch_vault_x.amount)?;
+            self.batch_transfer(self.batch_vault_y.to_account_info(), self.vault_y.to_account_info(), self.batch_vault_y.amount)?;
+
+            self.close_batch_vault(true)?;
+            self.close_batch_vault(false)?;
+            self.batch.close(self.payer.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn batch_transfer(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.batch.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let batch_id = self.batch.batch_id.to_le_bytes();
+        let seeds = &[&b"batch"[..], config_key.as_ref(), batch_id.as_ref(), &[self.batch.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)
+    }
+
+    pub fn close_batch_vault(&mut self, is_x: bool) -> Result<()> {
+        let account = match is_x {
+            true => self.batch_vault_x.to_account_info(),
+            false => self.batch_vault_y.to_account_info(),
+        };
+
+        let cpi_accounts = CloseAccount {
+            account,
+            destination: self.payer.to_account_info(),
+            authority: self.batch.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let batch_id = self.batch.batch_id.to_le_bytes();
+        let seeds = &[&b"batch"[..], config_key.as_ref(), batch_id.as_ref(), &[self.batch.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)
+    }
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, state::{Config, TraderPermit}};
+
+// the pool authority takes a permit away, the trader can still withdraw liquidity afterwards
+/*
+    accounts required:
+    - authority
+    - config
+    - permit
+*/
+#[derive(Accounts)]
+pub struct RevokePermit<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        seeds = [b"permit", config.key().as_ref(), permit.trader.as_ref()],
+        bump = permit.bump
+    )]
+    pub permit: Account<'info, TraderPermit>,
+}
+
+impl<'info> RevokePermit<'info> {
+    pub fn revoke_permit(&mut self) -> Result<()> {
+        require!(self.config.authority == Some(self.authority.key()), AmmError::InvalidAuthority);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,356 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{error::AmmError, state::{Batch, Config, DynamicFee, Locker, SwapIntent}};
+
+// permissionless crank that clears every intent of a batch at a single uniform price
+/*
+    accounts used:
+    - settler
+    - payer
+    - mint_x, mint_y
+    - config
+    - vault_x, vault_y
+    - batch
+    - batch_vault_x, batch_vault_y
+    - locker, locker_vault_x, locker_vault_y (only needed while config.boost_share is set)
+    - two programs
+    - remaining accounts: for every intent of the batch [intent, owner, owner_x, owner_y]
+*/
+#[derive(Accounts)]
+pub struct SettleBatch<'info> {
+    #[account(mut)]
+    pub settler: Signer<'info>,
+    /// CHECK: only receives the rent of the batch, checked against batch.payer
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        close = payer,
+        has_one = config,
+        has_one = payer,
+        seeds = [b"batch", config.key().as_ref(), batch.batch_id.to_le_bytes().as_ref()],
+        bump = batch.bump
+    )]
+    pub batch: Account<'info, Batch>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = batch,
+    )]
+    pub batch_vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = batch,
+    )]
+    pub batch_vault_y: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"locker", config.key().as_ref()],
+        bump = locker.bump
+    )]
+    pub locker: Option<Account<'info, Locker>>, // receives the boosted part of the fee for locked lp positions
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = locker,
+    )]
+    pub locker_vault_x: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = locker,
+    )]
+    pub locker_vault_y: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// an intent of the batch together with the accounts it is paid out to
+pub struct IntentFill<'info> {
+    pub intent: Account<'info, SwapIntent>,
+    pub owner: AccountInfo<'info>,
+    pub owner_x: AccountInfo<'info>,
+    pub owner_y: AccountInfo<'info>,
+    pub filled: bool,
+    pub amount_out: u64,
+}
+
+impl<'info> SettleBatch<'info> {
+    pub fn settle_batch(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(clock.slot >= self.batch.settle_slot, AmmError::BatchNotReady);
+
+        let mut fills = self.load_intents(remaining_accounts)?;
+        let (mut boost_x, mut boost_y) = (0u64, 0u64);
+
+        // a locked pool does not trade, every intent stays unfilled and everybody gets their tokens back
+        if !self.config.locked {
+            let fee = self.config.swap_fee(clock.unix_timestamp);
+            self.clear(&mut fills, fee)?;
+
+            // like in swap, part of the fee every filled intent paid can go to the locked lp positions
+            for fill in fills.iter().filter(|fill| fill.filled) {
+                let boost = self.config.boost_fee(self.locker.as_deref_mut(), fill.intent.is_x, fill.intent.amount, fee)?;
+                match fill.intent.is_x {
+                    true => boost_x = boost_x.checked_add(boost).ok_or(AmmError::Overflow)?,
+                    false => boost_y = boost_y.checked_add(boost).ok_or(AmmError::Overflow)?,
+                }
+            }
+        }
+
+        let (x, y) = (self.vault_x.amount, self.vault_y.amount);
+        let (mut x_out, mut y_out) = (0u64, 0u64);
+
+        // everything escrowed in the batch moves into the pool, fills and refunds are then paid from the pool vaults
+        self.move_batch_vault(true)?;
+        self.move_batch_vault(false)?;
+        self.pay_boosted_fee(true, boost_x)?;
+        self.pay_boosted_fee(false, boost_y)?;
+
+        for fill in fills.iter() {
+            // filled intents receive the other token, the rest get back exactly what they committed
+            let (pay_x, amount) = match fill.filled {
+                true => (!fill.intent.is_x, fill.amount_out),
+                false => (fill.intent.is_x, fill.intent.amount),
+            };
+
+            match pay_x {
+                true => x_out = x_out.checked_add(amount).ok_or(AmmError::Overflow)?,
+                false => y_out = y_out.checked_add(amount).ok_or(AmmError::Overflow)?,
+            }
+
+            self.pay_out(fill, pay_x, amount)?;
+        }
+
+        let x_after = x
+            .checked_add(self.batch.total_x_in)
+            .and_then(|x| x.checked_sub(x_out))
+            .and_then(|x| x.checked_sub(boost_x))
+            .ok_or(AmmError::Underflow)?;
+        let y_after = y
+            .checked_add(self.batch.total_y_in)
+            .and_then(|y| y.checked_sub(y_out))
+            .and_then(|y| y.checked_sub(boost_y))
+            .ok_or(AmmError::Underflow)?;
+
+        if let Some(dynamic_fee) = self.config.dynamic_fee.as_mut() {
+            if x_after > 0 && y_after > 0 {
+                dynamic_fee.record(DynamicFee::spot_price(x, y)?, DynamicFee::spot_price(x_after, y_after)?)?;
+            }
+        }
+
+        self.close_batch_vault(true)?;
+        self.close_batch_vault(false)?;
+
+        // the intents close last, once no more cpis need them
+        for fill in fills.iter() {
+            fill.intent.close(fill.owner.clone())?;
+        }
+
+        Ok(())
+    }
+
+    // every intent of the batch has to be passed, otherwise the uniform price would not account for all of them
+    pub fn load_intents(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<Vec<IntentFill<'info>>> {
+        require!(remaining_accounts.len() == self.batch.intent_count as usize * 4, AmmError::MissingIntents);
+
+        let mut fills: Vec<IntentFill<'info>> = Vec::with_capacity(remaining_accounts.len() / 4);
+
+        for accounts in remaining_accounts.chunks(4) {
+            let intent: Account<'info, SwapIntent> = Account::try_from(&accounts[0])?;
+
+            require!(intent.batch == self.batch.key(), AmmError::InvalidIntent);
+            require!(fills.iter().all(|fill| fill.intent.key() != intent.key()), AmmError::InvalidIntent);
+            require!(accounts[1].key() == intent.owner, AmmError::InvalidIntent);
+            require!(
+                accounts[2].key() == get_associated_token_address(&intent.owner, &self.mint_x.key())
+                    && accounts[3].key() == get_associated_token_address(&intent.owner, &self.mint_y.key()),
+                AmmError::InvalidIntent
+            );
+
+            fills.push(IntentFill {
+                intent,
+                owner: accounts[1].clone(),
+                owner_x: accounts[2].clone(),
+                owner_y: accounts[3].clone(),
+                filled: false,
+                amount_out: 0,
+            });
+        }
+
+        Ok(fills)
+    }
+
+    /*
+        uniform clearing price:
+        - the fee is taken from every intent's input and stays in the pool, like in swap
+        - with X and Y the inputs after fee of all filled intents, the batch clears at p = (y + Y) / (x + X) y per x,
+          x sellers receive amount * p of y and y sellers receive amount / p of x
+        - at that price the pool ends up at x' = y(x + X) / (y + Y) and y' = x(y + Y) / (x + X), so x' * y' = x * y and the pool itself trades on its curve
+        - intents whose min is not met at p are dropped and refunded, which moves p, so this repeats until no more intents are dropped
+        - intents are loaded unfilled, only clear marks them filled, so anything it does not reach is refunded
+    */
+    pub fn clear(&self, fills: &mut [IntentFill<'info>], fee: u16) -> Result<()> {
+        let (x, y) = (self.vault_x.amount as u128, self.vault_y.amount as u128);
+        require!(x > 0 && y > 0, AmmError::NoLiquidityInPool);
+
+        fills.iter_mut().for_each(|fill| fill.filled = true);
+
+        let after_fee = |amount: u64| amount as u128 * (10_000 - fee as u128) / 10_000;
+
+        loop {
+            let (mut x_in, mut y_in) = (0u128, 0u128);
+            for fill in fills.iter().filter(|fill| fill.filled) {
+                match fill.intent.is_x {
+                    true => x_in += after_fee(fill.intent.amount),
+                    false => y_in += after_fee(fill.intent.amount),
+                }
+            }
+
+            let price_num = y.checked_add(y_in).ok_or(AmmError::Overflow)?;
+            let price_den = x.checked_add(x_in).ok_or(AmmError::Overflow)?;
+
+            let mut dropped = false;
+            for fill in fills.iter_mut().filter(|fill| fill.filled) {
+                let amount_out = match fill.intent.is_x {
+                    true => after_fee(fill.intent.amount).checked_mul(price_num).ok_or(AmmError::Overflow)? / price_den,
+                    false => after_fee(fill.intent.amount).checked_mul(price_den).ok_or(AmmError::Overflow)? / price_num,
+                };
+                let amount_out = u64::try_from(amount_out).map_err(|_| AmmError::Overflow)?;
+
+                match amount_out > 0 && amount_out >= fill.intent.min {
+                    true => fill.amount_out = amount_out,
+                    false => {
+                        fill.filled = false;
+                        dropped = true;
+                    }
+                }
+            }
+
+            if !dropped {
+                return Ok(());
+            }
+        }
+    }
+
+    pub fn move_batch_vault(&mut self, is_x: bool) -> Result<()> {
+        let (from, to, amount) = match is_x {
+            true => (self.batch_vault_x.to_account_info(), self.vault_x.to_account_info(), self.batch_vault_x.amount),
+            false => (self.batch_vault_y.to_account_info(), self.vault_y.to_account_info(), self.batch_vault_y.amount),
+        };
+
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.batch.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let batch_id = self.batch.batch_id.to_le_bytes();
+        let seeds = &[&b"batch"[..], config_key.as_ref(), batch_id.as_ref(), &[self.batch.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)
+    }
+
+    pub fn pay_boosted_fee(&self, is_x: bool, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let (from, to) = match is_x {
+            true => (self.vault_x.to_account_info(), self.locker_vault_x.as_ref().ok_or(AmmError::LockerRequired)?.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.locker_vault_y.as_ref().ok_or(AmmError::LockerRequired)?.to_account_info()),
+        };
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        let seeds = &[&b"config"[..], &self.config.seed.to_le_bytes(), &[self.config.config_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)
+    }
+
+    // owner atas can be created by the settler in the same transaction if an owner closed theirs
+    pub fn pay_out(&self, fill: &IntentFill<'info>, is_x: bool, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let (from, to) = match is_x {
+            true => (self.vault_x.to_account_info(), fill.owner_x.clone()),
+            false => (self.vault_y.to_account_info(), fill.owner_y.clone()),
+        };
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        let seeds = &[&b"config"[..], &self.config.seed.to_le_bytes(), &[self.config.config_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)
+    }
+
+    pub fn close_batch_vault(&mut self, is_x: bool) -> Result<()> {
+        let account = match is_x {
+            true => self.batch_vault_x.to_account_info(),
+            false => self.batch_vault_y.to_account_info(),
+        };
+
+        let cpi_accounts = CloseAccount {
+            account,
+            destination: self.payer.to_account_info(),
+            authority: self.batch.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let batch_id = self.batch.batch_id.to_le_bytes();
+        let seeds = &[&b"batch"[..], config_key.as_ref(), batch_id.as_ref(), &[self.batch.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)
+    }
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{error::AmmError, state::{Config, Farm, FarmPosition}};
+
+// this instruction lets liquidity providers stake their lp tokens in the farm to earn rewards on top of swap fees
+/*
+    accounts required:
+    - user
+    - config
+    - mint_lp
+    - farm, position
+    - user_lp, farm_lp_vault
+    - the three programs
+*/
+#[derive(Accounts)]
+pub struct StakeLp<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + FarmPosition::INIT_SPACE,
+        seeds = [b"position", farm.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, FarmPosition>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user
+    )]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm
+    )]
+    pub farm_lp_vault: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> StakeLp<'info> {
+    pub fn stake_lp(&mut self, amount: u64, bumps: &StakeLpBumps) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(self.user_lp.amount >= amount, AmmError::InsufficientBalance);
+
+        // first stake for this user, so the position has to be filled in
+        if self.position.owner == Pubkey::default() {
+            self.position.set_inner(FarmPosition {
+                owner: self.user.key(),
+                farm: self.farm.key(),
+                amount: 0,
+                reward_debt: 0,
+                pending_rewards: 0,
+                bump: bumps.position,
+            });
+        }
+
+        self.farm.update_rewards(Clock::get()?.unix_timestamp)?;
+        self.position.accrue(self.farm.reward_per_share)?;
+
+        let cpi_accounts = Transfer {
+            from: self.user_lp.to_account_info(),
+            to: self.farm_lp_vault.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, amount)?;
+
+        self.position.amount = self.position.amount.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.farm.total_staked = self.farm.total_staked.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.position.sync_debt(self.farm.reward_per_share)
+    }
+}
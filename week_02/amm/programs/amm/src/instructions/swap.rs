@@ -5,7 +5,7 @@ use anchor_spl::{
 };
 use constant_product_curve::{ConstantProduct,LiquidityPair};
 
-use crate::{error::AmmError, state::Config};
+use crate::{error::AmmError, state::{Config, DynamicFee, Locker, TraderPermit}};
 
 // this instruction is for users, in order to swap their tokens 
 /*
@@ -15,6 +15,8 @@ use crate::{error::AmmError, state::Config};
     - config
     - vault_x, vault_y,
     - user_x, user_y
+    - locker, locker_vault_x, locker_vault_y (only needed while config.boost_share is set)
+    - permit (only needed while config.permissioned is set)
     - three instructions
 */
 #[derive(Accounts)]
@@ -30,6 +32,7 @@ pub struct Swap<'info> {
     )]
     pub mint_lp: Account<'info, Mint>,
     #[account(
+        mut, // mutable because the dynamic fee volatility is updated on every swap
         has_one = mint_x, // here has_one puts the check that this mint_x is the same one as mentioned in the config account struct
         has_one = mint_y,
         seeds =[b"config",config.seed.to_le_bytes().as_ref()],
@@ -62,6 +65,30 @@ pub struct Swap<'info> {
     )]
     pub user_y: Account<'info, TokenAccount>, //ata for mint_y for user
 
+    #[account(
+        mut,
+        seeds = [b"locker", config.key().as_ref()],
+        bump = locker.bump
+    )]
+    pub locker: Option<Account<'info, Locker>>, // receives the boosted part of the fee for locked lp positions
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = locker,
+    )]
+    pub locker_vault_x: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = locker,
+    )]
+    pub locker_vault_y: Option<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"permit", config.key().as_ref(), user.key().as_ref()],
+        bump = permit.bump
+    )]
+    pub permit: Option<Account<'info, TraderPermit>>, // only needed while config.permissioned is set
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -74,13 +101,19 @@ impl<'info> Swap<'info> {
         
         require!(self.config.locked==false,AmmError::PoolLocked);
         require!(amount>0, AmmError::InvalidAmount);
+        require!(!self.config.permissioned || self.permit.is_some(), AmmError::PermitRequired);
+
+        require!(self.config.batch_slots.is_none(), AmmError::BatchModeEnabled);
+
+        // with the dynamic fee on, the fee depends on how much the price moved recently
+        let fee = self.config.swap_fee(Clock::get()?.unix_timestamp);
 
         // This creates a constant product curve (x × y = k)
         let mut curve = ConstantProduct::init(
             self.vault_x.amount,
             self.vault_y.amount,
             self.mint_lp.supply,
-            self.config.fee,
+            fee,
             None
         ).map_err(|_| AmmError::CurveError)?;
 
@@ -95,14 +128,59 @@ impl<'info> Swap<'info> {
 
         require!(res.deposit != 0 && res.withdraw != 0, AmmError::InvalidAmount);
 
+        // part of the fee can go to the locked lp positions instead of staying in the pool
+        let boost = self.config.boost_fee(self.locker.as_deref_mut(), is_x, res.deposit, fee)?;
+
+        self.record_volatility(is_x, res.deposit - boost, res.withdraw)?;
+
         // Transfers tokens from user to vault (what they're selling)
-        self.deposit_tokens_being_sold(is_x, res.deposit)?;
+        self.deposit_tokens_being_sold(is_x, res.deposit - boost)?;
+        self.deposit_boosted_fee(is_x, boost)?;
         // Transfers tokens from vault to user (what they're buying)
         self.withdraw_tokens_being_bought(is_x, res.withdraw)?;
 
         Ok(())
     }
 
+    pub fn deposit_boosted_fee(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let (from, to) = match is_x {
+            true => (self.user_x.to_account_info(), self.locker_vault_x.as_ref().ok_or(AmmError::LockerRequired)?.to_account_info()),
+            false => (self.user_y.to_account_info(), self.locker_vault_y.as_ref().ok_or(AmmError::LockerRequired)?.to_account_info()),
+        };
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, amount)
+    }
+
+    // feeds the price movement of this swap into the dynamic fee accumulator
+    pub fn record_volatility(&mut self, is_x: bool, deposit: u64, withdraw: u64) -> Result<()> {
+        let (x, y) = (self.vault_x.amount, self.vault_y.amount);
+
+        if let Some(dynamic_fee) = self.config.dynamic_fee.as_mut() {
+            let (x_after, y_after) = match is_x {
+                true => (x.checked_add(deposit).ok_or(AmmError::Overflow)?, y.checked_sub(withdraw).ok_or(AmmError::Underflow)?),
+                false => (x.checked_sub(withdraw).ok_or(AmmError::Underflow)?, y.checked_add(deposit).ok_or(AmmError::Overflow)?),
+            };
+
+            dynamic_fee.record(
+                DynamicFee::spot_price(x, y)?,
+                DynamicFee::spot_price(x_after, y_after)?,
+            )?;
+        }
+
+        Ok(())
+    }
+
     pub fn deposit_tokens_being_sold(&mut self, is_x:bool, amount: u64)->Result<()>{
 
         let (from,to) = match is_x{
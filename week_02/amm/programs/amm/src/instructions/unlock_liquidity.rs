@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{error::AmmError, state::{Config, LockedPosition, Locker}};
+
+// once the term is over, this instruction returns the locked lp tokens together with the unclaimed boosted fees and closes the position
+/*
+    accounts required:
+    - user
+    - mint_x, mint_y, mint_lp
+    - config
+    - locker, position
+    - locker_lp_vault, locker_vault_x, locker_vault_y
+    - user_lp, user_x, user_y
+    - the three programs
+*/
+#[derive(Accounts)]
+pub struct UnlockLiquidity<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"locker", config.key().as_ref()],
+        bump = locker.bump
+    )]
+    pub locker: Account<'info, Locker>,
+
+    #[account(
+        mut,
+        close = user,
+        has_one = config,
+        constraint = position.owner == user.key() @ AmmError::InvalidAuthority,
+        seeds = [b"locked", config.key().as_ref(), user.key().as_ref(), position.seed.to_le_bytes().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, LockedPosition>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = locker
+    )]
+    pub locker_lp_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = locker
+    )]
+    pub locker_vault_x: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = locker
+    )]
+    pub locker_vault_y: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user
+    )]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user
+    )]
+    pub user_x: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user
+    )]
+    pub user_y: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> UnlockLiquidity<'info> {
+    pub fn unlock_liquidity(&mut self) -> Result<()> {
+        // there is no early exit, not even with a penalty
+        require!(Clock::get()?.unix_timestamp >= self.position.unlock_at, AmmError::LockNotExpired);
+
+        let (fees_x, fees_y) = self.position.pending(&self.locker)?;
+        let amount = self.position.amount;
+
+        self.locker.total_locked = self.locker.total_locked.checked_sub(amount).ok_or(AmmError::Underflow)?;
+
+        self.transfer_from_locker(self.locker_lp_vault.to_account_info(), self.user_lp.to_account_info(), amount)?;
+        self.transfer_from_locker(self.locker_vault_x.to_account_info(), self.user_x.to_account_info(), fees_x)?;
+        self.transfer_from_locker(self.locker_vault_y.to_account_info(), self.user_y.to_account_info(), fees_y)
+    }
+
+    pub fn transfer_from_locker(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.locker.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[&b"locker"[..], config_key.as_ref(), &[self.locker.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)
+    }
+}
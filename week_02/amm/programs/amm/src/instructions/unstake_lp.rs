@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{error::AmmError, state::{Config, Farm, FarmPosition}};
+
+// this instruction returns staked lp tokens from the farm, rewards earned so far stay claimable in the position
+/*
+    accounts required:
+    - user
+    - config
+    - mint_lp
+    - farm, position
+    - user_lp, farm_lp_vault
+    - the two programs
+*/
+#[derive(Accounts)]
+pub struct UnstakeLp<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        mut,
+        has_one = farm,
+        constraint = position.owner == user.key() @ AmmError::InvalidAuthority,
+        seeds = [b"position", farm.key().as_ref(), user.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, FarmPosition>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user
+    )]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm
+    )]
+    pub farm_lp_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> UnstakeLp<'info> {
+    pub fn unstake_lp(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(self.position.amount >= amount, AmmError::InsufficientBalance);
+
+        self.farm.update_rewards(Clock::get()?.unix_timestamp)?;
+        self.position.accrue(self.farm.reward_per_share)?;
+
+        let cpi_accounts = Transfer {
+            from: self.farm_lp_vault.to_account_info(),
+            to: self.user_lp.to_account_info(),
+            authority: self.farm.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let seeds = &[&b"farm"[..], config_key.as_ref(), &[self.farm.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+
+        self.position.amount -= amount;
+        self.farm.total_staked = self.farm.total_staked.checked_sub(amount).ok_or(AmmError::Underflow)?;
+        self.position.sync_debt(self.farm.reward_per_share)
+    }
+}
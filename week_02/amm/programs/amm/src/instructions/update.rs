@@ -1,8 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::{error::AmmError, Config};
+use crate::{error::AmmError, Config, DynamicFee, MAX_BOOST_SHARE};
 
-// this instruction can be used to lock or unlock amm pools
+// this instruction can be used to lock or unlock amm pools, to switch the dynamic fee, batch mode and allowlisting on or off, and to set the fee boost of locked liquidity
 /* 
     accounts required:
     - user
@@ -38,4 +38,69 @@ impl<'info> Update<'info> {
         self.config.locked = false;
         Ok(())
     }
+
+    pub fn set_dynamic_fee(&mut self, min_fee: u16, max_fee: u16, volatility_multiplier: u16, decay_period: i64) -> Result<()> {
+        require!(self.config.authority == Some(self.user.key()), AmmError::InvalidAuthority);
+        require!(min_fee <= max_fee && max_fee < 10_000, AmmError::InvalidFee);
+        require!(decay_period > 0, AmmError::InvalidDuration);
+
+        // reconfiguring keeps the volatility accumulated so far, so the fee does not drop in the middle of a volatile period
+        let (volatility, last_update) = match self.config.dynamic_fee {
+            Some(dynamic_fee) => (dynamic_fee.volatility, dynamic_fee.last_update),
+            None => (0, Clock::get()?.unix_timestamp),
+        };
+
+        self.config.dynamic_fee = Some(DynamicFee {
+            min_fee,
+            max_fee,
+            volatility_multiplier,
+            decay_period,
+            volatility,
+            last_update,
+        });
+        Ok(())
+    }
+
+    // share of each swap fee that goes to locked lp positions, 0 switches the boost off
+    pub fn set_boost_share(&mut self, boost_share: u16) -> Result<()> {
+        require!(self.config.authority == Some(self.user.key()), AmmError::InvalidAuthority);
+        require!(boost_share <= MAX_BOOST_SHARE, AmmError::InvalidFee);
+
+        self.config.boost_share = boost_share;
+        Ok(())
+    }
+
+    // swaps have to be committed to batches of batch_slots slots, and swap is rejected until batch mode is disabled
+    pub fn enable_batch_mode(&mut self, batch_slots: u64) -> Result<()> {
+        require!(self.config.authority == Some(self.user.key()), AmmError::InvalidAuthority);
+        require!(batch_slots > 0, AmmError::InvalidDuration);
+
+        self.config.batch_slots = Some(batch_slots);
+        Ok(())
+    }
+
+    // batches that are already open can still be settled afterwards
+    pub fn disable_batch_mode(&mut self) -> Result<()> {
+        require!(self.config.authority == Some(self.user.key()), AmmError::InvalidAuthority);
+        require!(self.config.batch_slots.is_some(), AmmError::BatchModeDisabled);
+
+        self.config.batch_slots = None;
+        Ok(())
+    }
+
+    // while permissioned, swap and deposit need a TraderPermit, withdraw stays open either way
+    pub fn set_permissioned(&mut self, permissioned: bool) -> Result<()> {
+        require!(self.config.authority == Some(self.user.key()), AmmError::InvalidAuthority);
+
+        self.config.permissioned = permissioned;
+        Ok(())
+    }
+
+    // swaps go back to charging the static config.fee
+    pub fn disable_dynamic_fee(&mut self) -> Result<()> {
+        require!(self.config.authority == Some(self.user.key()), AmmError::InvalidAuthority);
+
+        self.config.dynamic_fee = None;
+        Ok(())
+    }
 }
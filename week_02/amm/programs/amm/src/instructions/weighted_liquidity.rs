@@ -0,0 +1,218 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{burn, mint_to, transfer, Burn, Mint, MintTo, Token, TokenAccount, Transfer},
+};
+
+use crate::{error::AmmError, state::WeightedPool, weighted_math, MAX_RATIO, MINIMUM_LIQUIDITY};
+
+// this is helpful for liquidity providers of weighted pools, either with every asset in proportion or with a single asset
+/*
+    accounts in the context struct:
+    - user
+    - pool
+    - mint_lp, user_lp, locked_lp
+    - the three accounts
+    - remaining accounts: [vault, user ata] for every asset in proportional joins and exits, only for the chosen asset in single asset ones
+*/
+#[derive(Accounts)]
+pub struct WeightedLiquidity<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"weighted", pool.seed.to_le_bytes().as_ref()],
+        bump = pool.pool_bump
+    )]
+    pub pool: Account<'info, WeightedPool>,
+
+    #[account(
+        mut,
+        seeds = [b"lp", pool.key().as_ref()],
+        bump = pool.lp_bump
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+    )]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = pool,
+    )]
+    pub locked_lp: Account<'info, TokenAccount>, // holds the MINIMUM_LIQUIDITY of the first join, the pool never moves it
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> WeightedLiquidity<'info> {
+    // lp_amount is the lp the user wants, max_amounts caps what is taken of every asset
+    // the first join takes max_amounts as they are and lp_amount is the least lp it accepts
+    pub fn join(&mut self, lp_amount: u64, max_amounts: Vec<u64>, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(lp_amount > 0, AmmError::InvalidAmount);
+        require!(max_amounts.len() == self.pool.mints.len(), AmmError::InvalidAssetCount);
+        require!(remaining_accounts.len() == self.pool.mints.len() * 2, AmmError::InvalidAssetCount);
+
+        let supply = self.mint_lp.supply;
+
+        for (index, accounts) in remaining_accounts.chunks(2).enumerate() {
+            let balance = self.pool.load_asset(&self.pool.key(), &self.user.key(), index, &accounts[0], &accounts[1])?;
+
+            // like deposit, the first liquidity provider sets the initial balances and therefore the prices
+            // vault balances donated before the first join are ignored and end up shared by every lp
+            let amount = match supply == 0 {
+                true => max_amounts[index],
+                false => u64::try_from((balance as u128 * lp_amount as u128).div_ceil(supply as u128))
+                    .map_err(|_| AmmError::Overflow)?,
+            };
+
+            require!(amount > 0, AmmError::InvalidAmount);
+            require!(amount <= max_amounts[index], AmmError::SlippageExceeded);
+
+            self.deposit_token(&accounts[1], &accounts[0], amount)?;
+        }
+
+        // the first join mints the invariant of what it deposited instead of an amount of its choice,
+        // and MINIMUM_LIQUIDITY of it is locked, so a tiny first join can not make lp so scarce that later joins round away value
+        let lp_amount = match supply == 0 {
+            true => {
+                let minted = weighted_math::invariant(&max_amounts, &self.pool.weights)?;
+                require!(minted > MINIMUM_LIQUIDITY, AmmError::LiquidityLessThanMinimum);
+                require!(minted - MINIMUM_LIQUIDITY >= lp_amount, AmmError::SlippageExceeded);
+
+                self.mint_lp_token(self.locked_lp.to_account_info(), MINIMUM_LIQUIDITY)?;
+                minted - MINIMUM_LIQUIDITY
+            }
+            false => lp_amount,
+        };
+
+        self.mint_lp_token(self.user_lp.to_account_info(), lp_amount)
+    }
+
+    // deposits a single asset, the fee is charged on the part of amount_in that is not proportional to the pool
+    pub fn join_single(&mut self, index: u8, amount_in: u64, min_lp: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(amount_in > 0, AmmError::InvalidAmount);
+        require!(remaining_accounts.len() == 2, AmmError::InvalidAssetCount);
+        require!(self.mint_lp.supply > 0, AmmError::NoLiquidityInPool);
+
+        let index = index as usize;
+        let balance = self.pool.load_asset(&self.pool.key(), &self.user.key(), index, &remaining_accounts[0], &remaining_accounts[1])?;
+        let weight = self.pool.weights[index];
+
+        require!(balance > 0, AmmError::NoLiquidityInPool);
+        require!(amount_in as u128 <= balance as u128 * MAX_RATIO as u128 / 10_000, AmmError::MaxRatioExceeded);
+
+        let taxable = amount_in as u128 * (10_000 - weight as u128) / 10_000;
+        let fee = (taxable * self.pool.fee as u128).div_ceil(10_000) as u64;
+
+        let lp_amount = weighted_math::join_single_lp(balance, weight, self.mint_lp.supply, amount_in - fee)?;
+        require!(lp_amount > 0, AmmError::InvalidAmount);
+        require!(lp_amount >= min_lp, AmmError::SlippageExceeded);
+
+        self.deposit_token(&remaining_accounts[1], &remaining_accounts[0], amount_in)?;
+        self.mint_lp_token(self.user_lp.to_account_info(), lp_amount)
+    }
+
+    // burns lp_amount for every asset in proportion, min_amounts protects against slippage
+    pub fn exit(&mut self, lp_amount: u64, min_amounts: Vec<u64>, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(lp_amount > 0, AmmError::InvalidAmount);
+        require!(self.user_lp.amount >= lp_amount, AmmError::InsufficientBalance);
+        require!(min_amounts.len() == self.pool.mints.len(), AmmError::InvalidAssetCount);
+        require!(remaining_accounts.len() == self.pool.mints.len() * 2, AmmError::InvalidAssetCount);
+
+        let supply = self.mint_lp.supply;
+
+        for (index, accounts) in remaining_accounts.chunks(2).enumerate() {
+            let balance = self.pool.load_asset(&self.pool.key(), &self.user.key(), index, &accounts[0], &accounts[1])?;
+            let amount = (balance as u128 * lp_amount as u128 / supply as u128) as u64;
+
+            require!(amount >= min_amounts[index], AmmError::SlippageExceeded);
+
+            self.withdraw_token(&accounts[0], &accounts[1], amount)?;
+        }
+
+        self.burn(lp_amount)
+    }
+
+    // burns lp_amount for a single asset, the fee is charged on the part that is not proportional to the pool
+    pub fn exit_single(&mut self, index: u8, lp_amount: u64, min_out: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(lp_amount > 0, AmmError::InvalidAmount);
+        require!(self.user_lp.amount >= lp_amount, AmmError::InsufficientBalance);
+        require!(remaining_accounts.len() == 2, AmmError::InvalidAssetCount);
+
+        let index = index as usize;
+        let balance = self.pool.load_asset(&self.pool.key(), &self.user.key(), index, &remaining_accounts[0], &remaining_accounts[1])?;
+        let weight = self.pool.weights[index];
+
+        let amount = weighted_math::exit_single_out(balance, weight, self.mint_lp.supply, lp_amount)?;
+        let taxable = amount as u128 * (10_000 - weight as u128) / 10_000;
+        let fee = (taxable * self.pool.fee as u128).div_ceil(10_000) as u64;
+        let amount = amount - fee;
+
+        require!(amount as u128 <= balance as u128 * MAX_RATIO as u128 / 10_000, AmmError::MaxRatioExceeded);
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(amount >= min_out, AmmError::SlippageExceeded);
+
+        self.withdraw_token(&remaining_accounts[0], &remaining_accounts[1], amount)?;
+        self.burn(lp_amount)
+    }
+
+    pub fn deposit_token(&self, from: &AccountInfo<'info>, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_accounts = Transfer {
+            from: from.clone(),
+            to: to.clone(),
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, amount)
+    }
+
+    pub fn withdraw_token(&self, from: &AccountInfo<'info>, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_accounts = Transfer {
+            from: from.clone(),
+            to: to.clone(),
+            authority: self.pool.to_account_info(),
+        };
+
+        let seeds = &[&b"weighted"[..], &self.pool.seed.to_le_bytes(), &[self.pool.pool_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)
+    }
+
+    pub fn mint_lp_token(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_accounts = MintTo {
+            mint: self.mint_lp.to_account_info(),
+            to,
+            authority: self.pool.to_account_info(), // the pool is the mint authority
+        };
+
+        let seeds = &[&b"weighted"[..], &self.pool.seed.to_le_bytes(), &[self.pool.pool_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        mint_to(cpi_ctx, amount)
+    }
+
+    pub fn burn(&self, amount: u64) -> Result<()> {
+        let cpi_accounts = Burn {
+            mint: self.mint_lp.to_account_info(),
+            from: self.user_lp.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        burn(cpi_ctx, amount)
+    }
+}
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, Transfer};
+
+use crate::{error::AmmError, state::WeightedPool, weighted_math, MAX_RATIO};
+
+// this instruction swaps between any two assets of a weighted pool
+/*
+    accounts used:
+    - user
+    - pool
+    - token program
+    - remaining accounts: [vault_in, user_in, vault_out, user_out]
+*/
+#[derive(Accounts)]
+pub struct WeightedSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"weighted", pool.seed.to_le_bytes().as_ref()],
+        bump = pool.pool_bump
+    )]
+    pub pool: Account<'info, WeightedPool>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WeightedSwap<'info> {
+    // sells amount of the asset at index_in for the asset at index_out, min is the slippage protection like in swap
+    pub fn swap(&mut self, index_in: u8, index_out: u8, amount: u64, min: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(index_in != index_out, AmmError::InvalidToken);
+        require!(remaining_accounts.len() == 4, AmmError::InvalidAssetCount);
+
+        let (index_in, index_out) = (index_in as usize, index_out as usize);
+        let pool_key = self.pool.key();
+        let user_key = self.user.key();
+
+        let balance_in = self.pool.load_asset(&pool_key, &user_key, index_in, &remaining_accounts[0], &remaining_accounts[1])?;
+        let balance_out = self.pool.load_asset(&pool_key, &user_key, index_out, &remaining_accounts[2], &remaining_accounts[3])?;
+
+        require!(balance_in > 0 && balance_out > 0, AmmError::NoLiquidityInPool);
+        require!(amount as u128 <= balance_in as u128 * MAX_RATIO as u128 / 10_000, AmmError::MaxRatioExceeded);
+
+        // the fee stays in the pool, only the rest of amount is priced on the curve
+        let fee = (amount as u128 * self.pool.fee as u128).div_ceil(10_000) as u64;
+
+        let amount_out = weighted_math::swap_out(
+            balance_in,
+            self.pool.weights[index_in],
+            balance_out,
+            self.pool.weights[index_out],
+            amount - fee,
+        )?;
+
+        require!(amount_out as u128 <= balance_out as u128 * MAX_RATIO as u128 / 10_000, AmmError::MaxRatioExceeded);
+        require!(amount_out > 0, AmmError::InvalidAmount);
+        require!(amount_out >= min, AmmError::SlippageExceeded);
+
+        // tokens being sold go from the user to the vault
+        let cpi_accounts = Transfer {
+            from: remaining_accounts[1].clone(),
+            to: remaining_accounts[0].clone(),
+            authority: self.user.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, amount)?;
+
+        // tokens being bought go from the vault to the user
+        let cpi_accounts = Transfer {
+            from: remaining_accounts[2].clone(),
+            to: remaining_accounts[3].clone(),
+            authority: self.pool.to_account_info(),
+        };
+
+        let seeds = &[&b"weighted"[..], &self.pool.seed.to_le_bytes(), &[self.pool.pool_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount_out)
+    }
+}
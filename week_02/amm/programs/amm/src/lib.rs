@@ -4,6 +4,7 @@ pub mod constants;
 pub mod error;
 pub mod instructions;
 pub mod state;
+pub mod weighted_math;
 
 use anchor_lang::prelude::*;
 
@@ -60,4 +61,171 @@ pub mod amm {
     pub fn unlock(ctx: Context<Update>) -> Result<()> {
         ctx.accounts.unlock()
     }
+
+    pub fn set_dynamic_fee(
+        ctx: Context<Update>,
+        min_fee: u16,
+        max_fee: u16,
+        volatility_multiplier: u16,
+        decay_period: i64,
+    ) -> Result<()> {
+        ctx.accounts.set_dynamic_fee(min_fee, max_fee, volatility_multiplier, decay_period)
+    }
+
+    pub fn disable_dynamic_fee(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.disable_dynamic_fee()
+    }
+
+    pub fn set_boost_share(ctx: Context<Update>, boost_share: u16) -> Result<()> {
+        ctx.accounts.set_boost_share(boost_share)
+    }
+
+    pub fn enable_batch_mode(ctx: Context<Update>, batch_slots: u64) -> Result<()> {
+        ctx.accounts.enable_batch_mode(batch_slots)
+    }
+
+    pub fn disable_batch_mode(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.disable_batch_mode()
+    }
+
+    pub fn commit_swap(
+        ctx: Context<CommitSwap>,
+        batch_id: u64,
+        amount: u64,
+        is_x: bool,
+        min: u64,
+    ) -> Result<()> {
+        ctx.accounts.commit_swap(batch_id, amount, is_x, min, &ctx.bumps)
+    }
+
+    pub fn settle_batch<'info>(ctx: Context<'_, '_, 'info, 'info, SettleBatch<'info>>) -> Result<()> {
+        ctx.accounts.settle_batch(ctx.remaining_accounts)
+    }
+
+    pub fn refund_intent(ctx: Context<RefundIntent>) -> Result<()> {
+        ctx.accounts.refund_intent()
+    }
+
+    pub fn initialize_farm(ctx: Context<InitializeFarm>) -> Result<()> {
+        ctx.accounts.init_farm(&ctx.bumps)
+    }
+
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64, duration: i64) -> Result<()> {
+        ctx.accounts.fund_rewards(amount, duration)
+    }
+
+    pub fn stake_lp(ctx: Context<StakeLp>, amount: u64) -> Result<()> {
+        ctx.accounts.stake_lp(amount, &ctx.bumps)
+    }
+
+    pub fn unstake_lp(ctx: Context<UnstakeLp>, amount: u64) -> Result<()> {
+        ctx.accounts.unstake_lp(amount)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        ctx.accounts.claim_rewards()
+    }
+
+    pub fn initialize_weighted_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, InitializeWeightedPool<'info>>,
+        seed: u64,
+        fee: u16,
+        weights: Vec<u16>,
+    ) -> Result<()> {
+        ctx.accounts.init(seed, fee, weights, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn join_weighted_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WeightedLiquidity<'info>>,
+        lp_amount: u64,
+        max_amounts: Vec<u64>,
+    ) -> Result<()> {
+        ctx.accounts.join(lp_amount, max_amounts, ctx.remaining_accounts)
+    }
+
+    pub fn join_weighted_pool_single<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WeightedLiquidity<'info>>,
+        index: u8,
+        amount_in: u64,
+        min_lp: u64,
+    ) -> Result<()> {
+        ctx.accounts.join_single(index, amount_in, min_lp, ctx.remaining_accounts)
+    }
+
+    pub fn exit_weighted_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WeightedLiquidity<'info>>,
+        lp_amount: u64,
+        min_amounts: Vec<u64>,
+    ) -> Result<()> {
+        ctx.accounts.exit(lp_amount, min_amounts, ctx.remaining_accounts)
+    }
+
+    pub fn exit_weighted_pool_single<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WeightedLiquidity<'info>>,
+        index: u8,
+        lp_amount: u64,
+        min_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.exit_single(index, lp_amount, min_out, ctx.remaining_accounts)
+    }
+
+    pub fn swap_weighted<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WeightedSwap<'info>>,
+        index_in: u8,
+        index_out: u8,
+        amount: u64,
+        min: u64,
+    ) -> Result<()> {
+        ctx.accounts.swap(index_in, index_out, amount, min, ctx.remaining_accounts)
+    }
+
+    pub fn place_order(
+        ctx: Context<PlaceOrder>,
+        seed: u64,
+        is_x: bool,
+        amount: u64,
+        limit_price: u64,
+    ) -> Result<()> {
+        ctx.accounts.place_order(seed, is_x, amount, limit_price, &ctx.bumps)
+    }
+
+    pub fn fill_orders<'info>(ctx: Context<'_, '_, 'info, 'info, FillOrders<'info>>) -> Result<()> {
+        ctx.accounts.fill_orders(ctx.remaining_accounts)
+    }
+
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        ctx.accounts.cancel_order()
+    }
+
+    pub fn initialize_locker(ctx: Context<InitializeLocker>) -> Result<()> {
+        ctx.accounts.init_locker(&ctx.bumps)
+    }
+
+    pub fn lock_liquidity(ctx: Context<LockLiquidity>, seed: u64, amount: u64, duration: i64) -> Result<()> {
+        ctx.accounts.lock_liquidity(seed, amount, duration, &ctx.bumps)
+    }
+
+    pub fn claim_locked_fees(ctx: Context<ClaimLockedFees>) -> Result<()> {
+        ctx.accounts.claim_locked_fees()
+    }
+
+    pub fn unlock_liquidity(ctx: Context<UnlockLiquidity>) -> Result<()> {
+        ctx.accounts.unlock_liquidity()
+    }
+
+    pub fn set_permissioned(ctx: Context<Update>, permissioned: bool) -> Result<()> {
+        ctx.accounts.set_permissioned(permissioned)
+    }
+
+    pub fn issue_permit(ctx: Context<IssuePermit>) -> Result<()> {
+        ctx.accounts.issue_permit(&ctx.bumps)
+    }
+
+    pub fn revoke_permit(ctx: Context<RevokePermit>) -> Result<()> {
+        ctx.accounts.revoke_permit()
+    }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>, seed: u64) -> Result<()> {
+        ctx.accounts.migrate_config(seed)
+    }
 }
\ No newline at end of file
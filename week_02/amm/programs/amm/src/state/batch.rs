@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Batch {
+    pub config: Pubkey, // pool the batch settles against
+    pub batch_id: u64, // slot / batch_slots at the time the intents were committed
+    pub settle_slot: u64, // first slot at which the batch can be settled
+    pub payer: Pubkey, // whoever opened the batch, gets the rent back on settlement
+    pub total_x_in: u64, // x committed by all intents selling x
+    pub total_y_in: u64, // y committed by all intents selling y
+    pub intent_count: u8,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SwapIntent {
+    pub owner: Pubkey,
+    pub batch: Pubkey,
+    pub is_x: bool, // true when selling x for y, same meaning as in swap
+    pub amount: u64, // tokens escrowed in the batch vault
+    pub min: u64, // minimum the owner accepts in return, otherwise the intent is refunded
+    pub bump: u8,
+}
+
+/*
+- in batch mode swaps do not execute against the reserves right away. Every intent of the same batch window is escrowed in the batch vaults,
+  and once the window is over anyone can call settle_batch, which fills all of them at one uniform price.
+
+- since the whole batch clears at the same price, there is no ordering inside a batch to exploit, so sandwiching a swap is no longer possible.
+
+- a batch that is still not settled BATCH_REFUND_SLOTS after its settle_slot, for example because the pool was drained, can not trap the escrowed tokens:
+  refund_intent then returns each intent's tokens to its owner, and the last refund closes the batch.
+*/
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, PRICE_PRECISION};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct DynamicFee {
+    pub min_fee: u16, // fee charged when the pool has been calm, in basis points
+    pub max_fee: u16, // the fee never goes above this, no matter how volatile the pool is
+    pub volatility_multiplier: u16, // basis points of fee added per 100% (10_000 bps) of accumulated volatility
+    pub decay_period: i64, // seconds it takes for the accumulated volatility to fully decay
+    pub volatility: u64, // accumulated price movement in basis points, raised by swaps and decayed over time
+    pub last_update: i64, // last time the volatility was decayed
+}
+
+impl DynamicFee {
+    // volatility decays linearly, after decay_period seconds without swaps it is back to zero
+    pub fn decay(&mut self, now: i64) {
+        let elapsed = now.saturating_sub(self.last_update);
+
+        self.volatility = match elapsed >= self.decay_period {
+            true => 0,
+            false => {
+                (self.volatility as u128 * (self.decay_period - elapsed) as u128
+                    / self.decay_period as u128) as u64
+            }
+        };
+        self.last_update = now;
+    }
+
+    // current fee in basis points, always within [min_fee, max_fee]
+    pub fn fee(&self) -> u16 {
+        let extra = self.volatility as u128 * self.volatility_multiplier as u128 / 10_000;
+        let fee = (self.min_fee as u128).saturating_add(extra);
+
+        fee.min(self.max_fee as u128) as u16
+    }
+
+    // adds the price movement caused by a swap to the accumulator
+    pub fn record(&mut self, price_before: u128, price_after: u128) -> Result<()> {
+        require!(price_before > 0, AmmError::NoLiquidityInPool);
+
+        let moved = price_before.abs_diff(price_after)
+            .checked_mul(10_000)
+            .ok_or(AmmError::Overflow)?
+            / price_before;
+
+        self.volatility = self.volatility.saturating_add(u64::try_from(moved).unwrap_or(u64::MAX));
+        Ok(())
+    }
+
+    // spot price of x in terms of y, scaled by PRICE_PRECISION
+    pub fn spot_price(x: u64, y: u64) -> Result<u128> {
+        require!(x > 0, AmmError::NoLiquidityInPool);
+
+        Ok((y as u128)
+            .checked_mul(PRICE_PRECISION)
+            .ok_or(AmmError::Overflow)?
+            / x as u128)
+    }
+}
+
+/*
+- when dynamic_fee is set on the config, swaps ignore config.fee and use DynamicFee::fee() instead.
+
+- every swap first decays the accumulated volatility for the time that passed since the previous swap, then charges the resulting fee,
+  and finally adds how much the swap moved the spot price (in basis points) to the accumulator. A run of large swaps in a short time
+  therefore raises the fee up to max_fee, and once trading calms down the fee slides back to min_fee on its own.
+*/
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, REWARD_PRECISION};
+
+#[account]
+#[derive(InitSpace)]
+pub struct Farm {
+    pub config: Pubkey, // pool this farm is attached to
+    pub reward_mint: Pubkey, // mint the rewards are paid out in, can be any mint
+    pub reward_per_share: u128, // accumulated rewards per staked lp token, scaled by REWARD_PRECISION
+    pub reward_rate: u64, // reward tokens emitted per second
+    pub last_update: i64, // last time reward_per_share was brought up to date
+    pub period_end: i64, // emissions stop at this timestamp until the farm is funded again
+    pub total_staked: u64, // lp tokens currently staked in the farm
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct FarmPosition {
+    pub owner: Pubkey,
+    pub farm: Pubkey,
+    pub amount: u64, // lp tokens staked by the owner
+    pub reward_debt: u128, // part of amount * reward_per_share that has already been accounted for
+    pub pending_rewards: u64, // rewards accrued but not claimed yet
+    pub bump: u8,
+}
+
+impl Farm {
+    // brings reward_per_share up to date with everything emitted since last_update
+    pub fn update_rewards(&mut self, now: i64) -> Result<()> {
+        let until = now.min(self.period_end);
+
+        if until > self.last_update {
+            if self.total_staked > 0 {
+                let emitted = ((until - self.last_update) as u128)
+                    .checked_mul(self.reward_rate as u128)
+                    .ok_or(AmmError::Overflow)?;
+
+                let per_share = emitted
+                    .checked_mul(REWARD_PRECISION)
+                    .ok_or(AmmError::Overflow)?
+                    / self.total_staked as u128;
+
+                self.reward_per_share = self
+                    .reward_per_share
+                    .checked_add(per_share)
+                    .ok_or(AmmError::Overflow)?;
+            }
+            self.last_update = until;
+        }
+
+        Ok(())
+    }
+}
+
+impl FarmPosition {
+    // moves whatever the position earned since the last sync into pending_rewards
+    pub fn accrue(&mut self, reward_per_share: u128) -> Result<()> {
+        let pending = self
+            .accumulated(reward_per_share)?
+            .checked_sub(self.reward_debt)
+            .ok_or(AmmError::Underflow)?;
+
+        self.pending_rewards = self
+            .pending_rewards
+            .checked_add(u64::try_from(pending).map_err(|_| AmmError::Overflow)?)
+            .ok_or(AmmError::Overflow)?;
+
+        Ok(())
+    }
+
+    // has to be called every time amount changes, so old rewards are not paid twice
+    pub fn sync_debt(&mut self, reward_per_share: u128) -> Result<()> {
+        self.reward_debt = self.accumulated(reward_per_share)?;
+        Ok(())
+    }
+
+    fn accumulated(&self, reward_per_share: u128) -> Result<u128> {
+        Ok((self.amount as u128)
+            .checked_mul(reward_per_share)
+            .ok_or(AmmError::Overflow)?
+            / REWARD_PRECISION)
+    }
+}
+
+/*
+- the farm uses the usual reward-per-share accumulator: every second the farm emits reward_rate tokens, which are split between all staked lp tokens.
+  reward_per_share is the sum of those splits since the farm was created, so a position is owed amount * reward_per_share minus whatever was already accounted for (reward_debt).
+
+- staked lp tokens sit in an ata owned by the farm pda, and the reward tokens sit in another ata owned by the farm pda.
+*/
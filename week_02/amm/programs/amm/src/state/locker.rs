@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, REWARD_PRECISION};
+
+#[account]
+#[derive(InitSpace)]
+pub struct Locker {
+    pub config: Pubkey, // pool this locker belongs to
+    pub total_locked: u64, // lp tokens locked in all positions of the pool
+    pub fee_per_share_x: u128, // boosted x fees accumulated per locked lp token, scaled by REWARD_PRECISION
+    pub fee_per_share_y: u128, // boosted y fees accumulated per locked lp token, scaled by REWARD_PRECISION
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct LockedPosition {
+    pub owner: Pubkey,
+    pub config: Pubkey,
+    pub seed: u64, // lets an owner hold several positions with different terms
+    pub amount: u64, // lp tokens locked
+    pub unlock_at: i64, // the lp tokens can not be taken out before this timestamp
+    pub debt_x: u128, // part of amount * fee_per_share_x that was already paid out
+    pub debt_y: u128, // part of amount * fee_per_share_y that was already paid out
+    pub bump: u8,
+}
+
+impl Locker {
+    // spreads boosted fees over every locked lp token
+    pub fn distribute(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        require!(self.total_locked > 0, AmmError::NoLiquidityInPool);
+
+        let per_share = (amount as u128)
+            .checked_mul(REWARD_PRECISION)
+            .ok_or(AmmError::Overflow)?
+            / self.total_locked as u128;
+
+        let fee_per_share = match is_x {
+            true => &mut self.fee_per_share_x,
+            false => &mut self.fee_per_share_y,
+        };
+        *fee_per_share = fee_per_share.checked_add(per_share).ok_or(AmmError::Overflow)?;
+
+        Ok(())
+    }
+}
+
+impl LockedPosition {
+    // boosted fees earned since the last claim, in x and y
+    pub fn pending(&self, locker: &Locker) -> Result<(u64, u64)> {
+        let earned = |fee_per_share: u128, debt: u128| -> Result<u64> {
+            let accumulated = (self.amount as u128)
+                .checked_mul(fee_per_share)
+                .ok_or(AmmError::Overflow)?
+                / REWARD_PRECISION;
+
+            u64::try_from(accumulated.checked_sub(debt).ok_or(AmmError::Underflow)?)
+                .map_err(|_| AmmError::Overflow.into())
+        };
+
+        Ok((earned(locker.fee_per_share_x, self.debt_x)?, earned(locker.fee_per_share_y, self.debt_y)?))
+    }
+
+    pub fn sync_debt(&mut self, locker: &Locker) -> Result<()> {
+        self.debt_x = (self.amount as u128)
+            .checked_mul(locker.fee_per_share_x)
+            .ok_or(AmmError::Overflow)?
+            / REWARD_PRECISION;
+        self.debt_y = (self.amount as u128)
+            .checked_mul(locker.fee_per_share_y)
+            .ok_or(AmmError::Overflow)?
+            / REWARD_PRECISION;
+
+        Ok(())
+    }
+}
+
+/*
+- liquidity providers can lock lp tokens for a fixed term, there is no way to get them out early.
+
+- while config.boost_share is set, that share of every swap fee does not stay in the pool but goes to the locker vaults,
+  where it is split between the locked positions only. Locked lp tokens still own their part of the pool like any other lp token,
+  so locked positions earn their normal share of the fees plus the boosted share that unlocked liquidity gives up.
+*/
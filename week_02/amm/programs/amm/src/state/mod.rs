@@ -1,5 +1,28 @@
 use anchor_lang::prelude::*;
 
+use crate::error::AmmError;
+
+pub mod farm;
+pub use farm::*;
+
+pub mod dynamic_fee;
+pub use dynamic_fee::*;
+
+pub mod batch;
+pub use batch::*;
+
+pub mod weighted_pool;
+pub use weighted_pool::*;
+
+pub mod order;
+pub use order::*;
+
+pub mod locker;
+pub use locker::*;
+
+pub mod permit;
+pub use permit::*;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Config{
@@ -11,6 +34,45 @@ pub struct Config{
     pub locked: bool,
     pub config_bump: u8,
     pub lp_bump: u8,
+    // fields below were added after the first pools were deployed, they only go at the end so older configs keep their layout
+    // an older config is brought up to this size with migrate_config
+    pub dynamic_fee: Option<DynamicFee>, // when set, swaps charge a volatility based fee instead of the static fee
+    pub boost_share: u16, // share of every swap fee redirected to locked lp positions, in basis points
+    pub batch_slots: Option<u64>, // when set, swaps go through batches spanning this many slots
+    pub permissioned: bool, // when true, only traders holding a TraderPermit can swap and deposit
+}
+
+impl Config {
+    // fee for a swap happening now, the dynamic fee is decayed up to now before it is read
+    pub fn swap_fee(&mut self, now: i64) -> u16 {
+        match self.dynamic_fee.as_mut() {
+            Some(dynamic_fee) => {
+                dynamic_fee.decay(now);
+                dynamic_fee.fee()
+            }
+            None => self.fee,
+        }
+    }
+
+    // boost_share of the fee charged on amount, credited to the locked positions right away
+    // the caller moves that much into the locker vault instead of the pool, without locked lp it all stays in the pool
+    pub fn boost_fee(&self, locker: Option<&mut Locker>, is_x: bool, amount: u64, fee: u16) -> Result<u64> {
+        if self.boost_share == 0 {
+            return Ok(0);
+        }
+
+        let locker = locker.ok_or(AmmError::LockerRequired)?;
+        if locker.total_locked == 0 {
+            return Ok(0);
+        }
+
+        let boost = (amount as u128 * fee as u128 / 10_000 * self.boost_share as u128 / 10_000) as u64;
+        if boost > 0 {
+            locker.distribute(is_x, boost)?;
+        }
+
+        Ok(boost)
+    }
 }
 
 /*
@@ -28,4 +90,14 @@ When None, the pool becomes "unlocked" or decentralized - no single entity can c
 - locked: bool - A safety mechanism that can pause all trading activity. When true, swaps are disabled but liquidity operations might still work.
 
 - config_bump: u8 & lp_bump: u8 - These store the "bump seeds" used to generate PDAs for the config account itself and the LP (liquidity provider) token mint. Storing these saves computation on subsequent operations since you don't need to derive them again.
+
+- dynamic_fee: Option<DynamicFee> - Optional volatility based fee. When Some, swaps charge a fee between min_fee and max_fee that rises with recent price movement and decays over time, and the static fee above is ignored.
+
+- boost_share: u16 - Share of every swap fee, in basis points, that is taken away from the pool and split between time-locked lp positions only. 0 keeps all fees in the pool.
+
+- batch_slots: Option<u64> - Optional MEV resistant mode. When Some, swap is disabled and trades are committed as intents to a batch covering batch_slots slots, which settle_batch later clears at a single uniform price.
+
+- permissioned: bool - Optional allowlisting for permissioned pools. When true, swap, commit_swap, place_order and deposit require a TraderPermit that the authority issued to the trader. withdraw always stays open so liquidity providers can exit.
+
+- Configs created before dynamic_fee, boost_share, batch_slots and permissioned existed are shorter than this struct. migrate_config reallocates them, and the zeroed new bytes read as None, 0, None and false, so every feature stays off until the authority switches it on.
 */
\ No newline at end of file
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct LimitOrder {
+    pub owner: Pubkey,
+    pub config: Pubkey, // pool the order rests on
+    pub seed: u64, // lets an owner keep several orders on the same pool
+    pub is_x: bool, // true when selling x for y, same meaning as in swap
+    pub amount: u64, // tokens escrowed in the order vault
+    pub limit_price: u64, // price of x in y scaled by PRICE_PRECISION, the order fills at this price or better
+    pub bump: u8,
+}
+
+/*
+- an order selling x fills once the pool's spot price of x rises to limit_price, an order selling y once it drops to limit_price.
+  The owner always receives at least amount * limit_price (or amount / limit_price when selling y) after fees and the keeper tip.
+
+- the tokens being sold are escrowed in an ata owned by the order pda until the order is filled or cancelled.
+*/
@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct TraderPermit {
+    pub config: Pubkey, // pool the permit is valid for
+    pub trader: Pubkey, // wallet allowed to swap and deposit while the pool is permissioned
+    pub bump: u8,
+}
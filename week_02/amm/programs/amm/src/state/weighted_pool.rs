@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::get_associated_token_address, token::TokenAccount};
+
+use crate::{error::AmmError, MAX_WEIGHTED_ASSETS};
+
+#[account]
+#[derive(InitSpace)]
+pub struct WeightedPool {
+    pub seed: u64, // allows us to have multiple weighted pools, same as Config
+    #[max_len(MAX_WEIGHTED_ASSETS)]
+    pub mints: Vec<Pubkey>, // the assets in the pool, the vault of each one is the ata of the pool pda
+    #[max_len(MAX_WEIGHTED_ASSETS)]
+    pub weights: Vec<u16>, // weight of each asset in basis points, they add up to 10_000
+    pub fee: u16,
+    pub pool_bump: u8,
+    pub lp_bump: u8,
+}
+
+impl WeightedPool {
+    // checks a [vault, user token account] pair from remaining_accounts against the asset at index and returns the vault balance
+    pub fn load_asset<'info>(
+        &self,
+        pool: &Pubkey,
+        user: &Pubkey,
+        index: usize,
+        vault: &'info AccountInfo<'info>,
+        user_ata: &'info AccountInfo<'info>,
+    ) -> Result<u64> {
+        let mint = self.mints.get(index).ok_or(AmmError::InvalidToken)?;
+        require!(vault.key() == get_associated_token_address(pool, mint), AmmError::InvalidToken);
+
+        let user_ata: Account<'info, TokenAccount> = Account::try_from(user_ata)?;
+        require!(user_ata.mint == *mint && user_ata.owner == *user, AmmError::InvalidToken);
+
+        let vault: Account<'info, TokenAccount> = Account::try_from(vault)?;
+        Ok(vault.amount)
+    }
+}
+
+/*
+- a weighted pool holds 2 to 8 assets, and instead of x * y = k it keeps the product of every balance raised to its weight constant.
+  A 80/20 pool for example keeps 80% of its value in the first asset, which makes it possible to run index like baskets.
+
+- since the number of assets varies, vaults and user token accounts are not part of the account structs but passed through remaining_accounts,
+  in the same order as mints.
+*/
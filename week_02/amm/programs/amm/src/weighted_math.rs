@@ -0,0 +1,267 @@
+use anchor_lang::prelude::*;
+
+use crate::error::AmmError;
+
+// fixed point math for the weighted pools, every value is scaled by ONE
+pub const ONE: i128 = 1_000_000_000_000;
+const LN_2: i128 = 693_147_180_560;
+
+// relative error allowed for pow, results are nudged by this much in favor of the pool
+const POW_ERROR: i128 = 10_000;
+
+// natural logarithm, x has to be greater than zero
+pub fn ln(x: i128) -> Result<i128> {
+    require!(x > 0, AmmError::InvalidAmount);
+
+    // x = m * 2^k with m in [1, 2)
+    let k = (127 - x.leading_zeros() as i128) - (127 - ONE.leading_zeros() as i128);
+    let mut m = match k >= 0 {
+        true => x >> k,
+        false => x << -k,
+    };
+    let mut k = k;
+    while m >= 2 * ONE {
+        m >>= 1;
+        k += 1;
+    }
+    while m < ONE {
+        m <<= 1;
+        k -= 1;
+    }
+
+    // ln(m) = 2 * (z + z^3 / 3 + z^5 / 5 + ...) with z = (m - 1) / (m + 1), which converges fast since z < 1/3
+    let z = (m - ONE) * ONE / (m + ONE);
+    let z_squared = z * z / ONE;
+    let (mut term, mut sum, mut n) = (z, 0, 1);
+    while term != 0 {
+        sum += term / n;
+        term = term * z_squared / ONE;
+        n += 2;
+    }
+
+    Ok(k * LN_2 + 2 * sum)
+}
+
+// e^y, small enough results are rounded down to zero
+pub fn exp(y: i128) -> Result<i128> {
+    require!(y <= 40 * ONE, AmmError::Overflow);
+    if y < -30 * ONE {
+        return Ok(0);
+    }
+
+    // y = k * ln(2) + r with r in [0, ln(2))
+    let k = y.div_euclid(LN_2);
+    let r = y - k * LN_2;
+
+    let (mut term, mut sum, mut n) = (ONE, ONE, 1);
+    while term != 0 {
+        term = term * r / ONE / n;
+        sum += term;
+        n += 1;
+    }
+
+    Ok(match k >= 0 {
+        true => sum << k,
+        false => sum >> -k,
+    })
+}
+
+// base^exponent rounded up, so amounts the pool pays out based on it are rounded down
+pub fn pow_up(base: i128, exponent: i128) -> Result<i128> {
+    let power = pow(base, exponent)?;
+    Ok(power + power * POW_ERROR / ONE + 1)
+}
+
+// base^exponent rounded down, so amounts the pool mints based on it are rounded down
+pub fn pow_down(base: i128, exponent: i128) -> Result<i128> {
+    let power = pow(base, exponent)?;
+    Ok((power - power * POW_ERROR / ONE - 1).max(0))
+}
+
+fn pow(base: i128, exponent: i128) -> Result<i128> {
+    let log = ln(base)?.checked_mul(exponent).ok_or(AmmError::Overflow)? / ONE;
+    exp(log)
+}
+
+// out = B_out * (1 - (B_in / (B_in + a_in)) ^ (w_in / w_out)), a_in is the amount after fee
+pub fn swap_out(balance_in: u64, weight_in: u16, balance_out: u64, weight_out: u16, amount_in: u64) -> Result<u64> {
+    let balance_in = balance_in as i128;
+    let base = (balance_in * ONE + balance_in + amount_in as i128 - 1) / (balance_in + amount_in as i128);
+    let exponent = weight_in as i128 * ONE / weight_out as i128;
+
+    let power = pow_up(base, exponent)?.min(ONE);
+    Ok((balance_out as i128 * (ONE - power) / ONE) as u64)
+}
+
+// lp minted for a single asset deposit: supply * ((1 + a_in / B) ^ w - 1), a_in is the amount after fee and w in basis points
+pub fn join_single_lp(balance: u64, weight: u16, supply: u64, amount_in: u64) -> Result<u64> {
+    let ratio = (balance as i128 + amount_in as i128) * ONE / balance as i128;
+    let exponent = weight as i128 * ONE / 10_000;
+
+    let power = pow_down(ratio, exponent)?;
+    match power > ONE {
+        true => u64::try_from(supply as i128 * (power - ONE) / ONE).map_err(|_| AmmError::Overflow.into()),
+        false => Ok(0),
+    }
+}
+
+// tokens paid for burning lp into a single asset, before fee: B * (1 - (1 - lp / supply) ^ (1 / w)), w in basis points
+pub fn exit_single_out(balance: u64, weight: u16, supply: u64, lp_amount: u64) -> Result<u64> {
+    require!(lp_amount < supply, AmmError::InsufficientBalance);
+
+    let supply = supply as i128;
+    let ratio = ((supply - lp_amount as i128) * ONE + supply - 1) / supply;
+    let exponent = 10_000 * ONE / weight as i128;
+
+    let power = pow_up(ratio, exponent)?.min(ONE);
+    Ok((balance as i128 * (ONE - power) / ONE) as u64)
+}
+
+// V = B_1^w_1 * ... * B_n^w_n in token units, rounded down like pow_down, weights in basis points
+pub fn invariant(balances: &[u64], weights: &[u16]) -> Result<u64> {
+    let mut log = 0i128;
+    for (balance, weight) in balances.iter().zip(weights) {
+        require!(*balance > 0, AmmError::InvalidAmount);
+        log += ln(*balance as i128 * ONE)? * *weight as i128 / 10_000;
+    }
+
+    // exp only goes up to e^40, larger invariants are computed as e^(log - 20) * e^20
+    let value = match log > 30 * ONE {
+        true => exp(log - 20 * ONE)?.checked_mul(exp(20 * ONE)? / ONE).ok_or(AmmError::Overflow)?,
+        false => exp(log)?,
+    };
+    let value = (value - value * POW_ERROR / ONE - 1).max(0);
+
+    u64::try_from(value / ONE).map_err(|_| AmmError::Overflow.into())
+}
+
+/*
+- weighted pools follow the balancer invariant V = B_1^w_1 * B_2^w_2 * ... * B_n^w_n, constant product is the special case of two assets at 50/50.
+
+- pow is computed as exp(exponent * ln(base)) with fixed point series, which is only approximate, so every result the pool pays out or mints from
+  is rounded against the user by POW_ERROR. Together with the fees this means V can only grow.
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // fixed point value of a float, for comparing against f64 math
+    fn fixed(value: f64) -> i128 {
+        (value * ONE as f64).round() as i128
+    }
+
+    fn assert_close(actual: i128, expected: f64, tolerance: i128) {
+        let expected = fixed(expected);
+        assert!((actual - expected).abs() <= tolerance, "{actual} is not within {tolerance} of {expected}");
+    }
+
+    #[test]
+    fn ln_matches_known_values() {
+        assert_eq!(ln(ONE).unwrap(), 0);
+        assert_close(ln(2 * ONE).unwrap(), std::f64::consts::LN_2, 10);
+        assert_close(ln(fixed(std::f64::consts::E)).unwrap(), 1.0, 10);
+        assert_close(ln(ONE / 1_000).unwrap(), 0.001f64.ln(), 100);
+        assert_close(ln(1_000_000 * ONE).unwrap(), 1_000_000f64.ln(), 100);
+        assert!(ln(0).is_err() && ln(-ONE).is_err());
+    }
+
+    #[test]
+    fn exp_matches_known_values() {
+        assert_eq!(exp(0).unwrap(), ONE);
+        assert_close(exp(ONE).unwrap(), std::f64::consts::E, 100);
+        assert_close(exp(-ONE).unwrap(), (-1f64).exp(), 100);
+        // the error of the series is scaled up by the 2^k shift, still about 1e-11 relative
+        assert_close(exp(10 * ONE).unwrap(), 10f64.exp(), 1_000_000);
+        assert_eq!(exp(-31 * ONE).unwrap(), 0);
+        assert!(exp(41 * ONE).is_err());
+    }
+
+    #[test]
+    fn pow_is_rounded_in_favor_of_the_pool() {
+        for (base, exponent) in [(4.0, 0.5), (2.0, 3.0), (0.5, 0.25), (1.5, 4.0), (0.9, 9.0)] {
+            let expected = fixed(f64::powf(base, exponent));
+            let up = pow_up(fixed(base), fixed(exponent)).unwrap();
+            let down = pow_down(fixed(base), fixed(exponent)).unwrap();
+
+            assert!(down < expected && expected < up, "{down} < {expected} < {up} does not hold for {base}^{exponent}");
+            // both stay within twice POW_ERROR of the exact power
+            assert!((up - expected) * ONE <= 2 * POW_ERROR * expected);
+            assert!((expected - down) * ONE <= 2 * POW_ERROR * expected);
+        }
+    }
+
+    #[test]
+    fn invariant_is_the_weighted_geometric_mean() {
+        // sqrt(1_000_000 * 4_000_000) and 16^0.25 * 81^0.75
+        let value = invariant(&[1_000_000, 4_000_000], &[5_000, 5_000]).unwrap();
+        assert!((1_999_970..2_000_000).contains(&value), "{value}");
+        let value = invariant(&[16_000_000, 81_000_000], &[2_500, 7_500]).unwrap();
+        assert!((53_999_000..54_000_000).contains(&value), "{value}");
+
+        // above e^30 the exponent is split, the result still rounds down
+        let value = invariant(&[u64::MAX, u64::MAX], &[5_000, 5_000]).unwrap();
+        assert!(value < u64::MAX && value > u64::MAX / 10_000 * 9_999, "{value}");
+    }
+
+    #[test]
+    fn equal_weights_swap_like_constant_product() {
+        // x * y = k gives 1_000_000 * 100_000 / 1_100_000 = 90_909.09
+        let out = swap_out(1_000_000, 5_000, 1_000_000, 5_000, 100_000).unwrap();
+        assert!((90_890..=90_909).contains(&out), "{out}");
+    }
+
+    proptest! {
+        // V = B_in^w_in * B_out^w_out never decreases, compared in log space
+        #[test]
+        fn swap_never_decreases_the_invariant(
+            balance_in in 1_000u64..1_000_000_000_000,
+            balance_out in 1_000u64..1_000_000_000_000,
+            weight_in in 100u16..9_900,
+            share in 1u64..3_000,
+        ) {
+            let weight_out = 10_000 - weight_in;
+            let amount_in = (balance_in as u128 * share as u128 / 10_000) as u64;
+            let out = swap_out(balance_in, weight_in, balance_out, weight_out, amount_in).unwrap();
+
+            prop_assert!(out < balance_out);
+            let gained = weight_in as f64 * (amount_in as f64 / balance_in as f64).ln_1p();
+            let lost = weight_out as f64 * (-(out as f64) / balance_out as f64).ln_1p();
+            prop_assert!(gained + lost >= 0.0, "V decreased by {}", gained + lost);
+        }
+
+        // V per lp token never decreases: ((B + a) / B)^w >= (supply + lp) / supply
+        #[test]
+        fn join_single_never_dilutes_lp(
+            balance in 1_000u64..1_000_000_000_000,
+            supply in 1_000u64..1_000_000_000_000,
+            weight in 100u16..9_900,
+            share in 1u64..3_000,
+        ) {
+            let amount_in = (balance as u128 * share as u128 / 10_000) as u64;
+            let lp = join_single_lp(balance, weight, supply, amount_in).unwrap();
+
+            let value = weight as f64 / 10_000.0 * (amount_in as f64 / balance as f64).ln_1p();
+            let shares = (lp as f64 / supply as f64).ln_1p();
+            prop_assert!(value >= shares, "lp value dropped by {}", shares - value);
+        }
+
+        // V per lp token never decreases: ((B - out) / B)^w >= (supply - lp) / supply
+        #[test]
+        fn exit_single_never_dilutes_lp(
+            balance in 1_000u64..1_000_000_000_000,
+            supply in 1_000u64..1_000_000_000_000,
+            weight in 100u16..9_900,
+            share in 1u64..3_000,
+        ) {
+            let lp_amount = (supply as u128 * share as u128 / 10_000).max(1) as u64;
+            let out = exit_single_out(balance, weight, supply, lp_amount).unwrap();
+
+            prop_assert!(out < balance);
+            let value = weight as f64 / 10_000.0 * (-(out as f64) / balance as f64).ln_1p();
+            let shares = (-(lp_amount as f64) / supply as f64).ln_1p();
+            prop_assert!(value >= shares, "lp value dropped by {}", shares - value);
+        }
+    }
+}
@@ -0,0 +1,1310 @@
+use amm::{error::AmmError, Config};
+use anchor_lang::{
+    error::ERROR_CODE_OFFSET,
+    prelude::{AccountInfo, Clock, Pubkey},
+    solana_program::{entrypoint::ProgramResult, program_pack::Pack},
+    system_program, AccountDeserialize, Discriminator, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{get_associated_token_address, spl_associated_token_account},
+    token::spl_token,
+};
+use proptest::prelude::*;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use solana_system_interface::instruction as system_instruction;
+
+const SEED: u64 = 42;
+const WEIGHTED_SEED: u64 = 43;
+const FEE: u16 = 30;
+const USER_FUNDS: u64 = 10_000_000_000;
+
+// anchor's entrypoint wants the account slice to live as long as the accounts in it
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    amm::entry(program_id, accounts, data)
+}
+
+struct Pool {
+    ctx: ProgramTestContext,
+    authority: Keypair,
+    user: Keypair,
+    mint_x: Pubkey,
+    mint_y: Pubkey,
+    config: Pubkey,
+    mint_lp: Pubkey,
+    vault_x: Pubkey,
+    vault_y: Pubkey,
+    user_x: Pubkey,
+    user_y: Pubkey,
+    user_lp: Pubkey,
+    permit: Option<Pubkey>, // passed to swap and deposit once issued
+    locker: Option<Pubkey>, // passed to everything that charges a swap fee once initialized
+    nonce: u32,
+}
+
+impl Pool {
+    // creates two mock mints, funds the user with both and initializes a pool for them
+    async fn new() -> Pool {
+        let mut program_test = ProgramTest::new("amm", amm::ID, processor!(process_instruction));
+        program_test.prefer_bpf(false);
+        let ctx = program_test.start_with_context().await;
+
+        let config = Pubkey::find_program_address(&[b"config", SEED.to_le_bytes().as_ref()], &amm::ID).0;
+        let mint_lp = Pubkey::find_program_address(&[b"lp", config.as_ref()], &amm::ID).0;
+
+        let mut pool = Pool {
+            ctx,
+            authority: Keypair::new(),
+            user: Keypair::new(),
+            mint_x: Pubkey::default(),
+            mint_y: Pubkey::default(),
+            config,
+            mint_lp,
+            vault_x: Pubkey::default(),
+            vault_y: Pubkey::default(),
+            user_x: Pubkey::default(),
+            user_y: Pubkey::default(),
+            user_lp: get_associated_token_address(&Pubkey::default(), &mint_lp),
+            permit: None,
+            locker: None,
+            nonce: 0,
+        };
+
+        pool.airdrop(&pool.authority.pubkey(), 10_000_000_000).await;
+        pool.airdrop(&pool.user.pubkey(), 10_000_000_000).await;
+
+        pool.mint_x = pool.create_mint().await;
+        pool.mint_y = pool.create_mint().await;
+        pool.vault_x = get_associated_token_address(&config, &pool.mint_x);
+        pool.vault_y = get_associated_token_address(&config, &pool.mint_y);
+        let user = pool.user.pubkey();
+        pool.user_x = pool.create_funded_ata(&user, pool.mint_x, USER_FUNDS).await;
+        pool.user_y = pool.create_funded_ata(&user, pool.mint_y, USER_FUNDS).await;
+        pool.user_lp = get_associated_token_address(&pool.user.pubkey(), &mint_lp);
+
+        pool.initialize(FEE).await.unwrap();
+        pool
+    }
+
+    async fn airdrop(&mut self, to: &Pubkey, lamports: u64) {
+        let payer = self.ctx.payer.insecure_clone();
+        let ix = system_instruction::transfer(&payer.pubkey(), to, lamports);
+        self.send(ix, &[&payer]).await.unwrap();
+    }
+
+    async fn create_mint(&mut self) -> Pubkey {
+        let payer = self.ctx.payer.insecure_clone();
+        let mint = Keypair::new();
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &payer.pubkey(),
+                    &mint.pubkey(),
+                    rent.minimum_balance(spl_token::state::Mint::LEN),
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::ID,
+                ),
+                spl_token::instruction::initialize_mint2(&spl_token::ID, &mint.pubkey(), &payer.pubkey(), None, 6).unwrap(),
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &mint],
+            self.ctx.last_blockhash,
+        );
+        self.ctx.banks_client.process_transaction(tx).await.unwrap();
+
+        mint.pubkey()
+    }
+
+    async fn create_funded_ata(&mut self, owner: &Pubkey, mint: Pubkey, amount: u64) -> Pubkey {
+        let payer = self.ctx.payer.insecure_clone();
+        let ata = get_associated_token_address(owner, &mint);
+
+        let create = spl_associated_token_account::instruction::create_associated_token_account(&payer.pubkey(), owner, &mint, &spl_token::ID);
+        self.send(create, &[&payer]).await.unwrap();
+
+        let mint_to = spl_token::instruction::mint_to(&spl_token::ID, &mint, &ata, &payer.pubkey(), &[], amount).unwrap();
+        self.send(mint_to, &[&payer]).await.unwrap();
+
+        ata
+    }
+
+    // the nonce makes otherwise identical transactions unique, so they are not rejected as already processed
+    async fn send(&mut self, ix: Instruction, signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        self.nonce += 1;
+        let unique = ComputeBudgetInstruction::set_compute_unit_limit(1_000_000 + self.nonce);
+
+        let tx = Transaction::new_signed_with_payer(&[unique, ix], Some(&signers[0].pubkey()), signers, self.ctx.last_blockhash);
+        self.ctx.banks_client.process_transaction(tx).await
+    }
+
+    async fn initialize(&mut self, fee: u16) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::Initialize {
+                initializer: authority.pubkey(),
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp,
+                config: self.config,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::Initialize { seed: SEED, fee, authority: Some(authority.pubkey()) }.data(),
+        };
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn deposit(&mut self, amount: u64, max_x: u64, max_y: u64) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::Deposit {
+                user: user.pubkey(),
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp,
+                config: self.config,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                user_x: self.user_x,
+                user_y: self.user_y,
+                user_lp: self.user_lp,
+                permit: self.permit,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::Deposit { amount, max_x, max_y }.data(),
+        };
+        self.send(ix, &[&user]).await
+    }
+
+    async fn swap(&mut self, amount: u64, is_x: bool, min: u64) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::Swap {
+                user: user.pubkey(),
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp,
+                config: self.config,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                user_x: self.user_x,
+                user_y: self.user_y,
+                locker: self.locker,
+                locker_vault_x: self.locker_vault(self.mint_x),
+                locker_vault_y: self.locker_vault(self.mint_y),
+                permit: self.permit,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::Swap { amount, is_x, min }.data(),
+        };
+        self.send(ix, &[&user]).await
+    }
+
+    async fn withdraw(&mut self, amount: u64, min_x: u64, min_y: u64) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::Withdraw {
+                user: user.pubkey(),
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                config: self.config,
+                mint_lp: self.mint_lp,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                user_x: self.user_x,
+                user_y: self.user_y,
+                user_lp: self.user_lp,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::Withdraw { amount, min_x, min_y }.data(),
+        };
+        self.send(ix, &[&user]).await
+    }
+
+    async fn update(&mut self, signer: &Keypair, lock: bool) -> Result<(), BanksClientError> {
+        let accounts = amm::accounts::Update { user: signer.pubkey(), config: self.config }.to_account_metas(None);
+        let data = match lock {
+            true => amm::instruction::Lock {}.data(),
+            false => amm::instruction::Unlock {}.data(),
+        };
+        self.send(Instruction { program_id: amm::ID, accounts, data }, &[signer]).await
+    }
+
+    async fn set_permissioned(&mut self, permissioned: bool) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::Update { user: authority.pubkey(), config: self.config }.to_account_metas(None),
+            data: amm::instruction::SetPermissioned { permissioned }.data(),
+        };
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn issue_permit(&mut self) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let permit = Pubkey::find_program_address(&[b"permit", self.config.as_ref(), self.user.pubkey().as_ref()], &amm::ID).0;
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::IssuePermit {
+                authority: authority.pubkey(),
+                trader: self.user.pubkey(),
+                config: self.config,
+                permit,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::IssuePermit {}.data(),
+        };
+        self.send(ix, &[&authority]).await?;
+
+        self.permit = Some(permit);
+        Ok(())
+    }
+
+    async fn revoke_permit(&mut self) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let permit = self.permit.expect("no permit issued");
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::RevokePermit { authority: authority.pubkey(), config: self.config, permit }.to_account_metas(None),
+            data: amm::instruction::RevokePermit {}.data(),
+        };
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn set_batch_mode(&mut self, batch_slots: Option<u64>) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let data = match batch_slots {
+            Some(batch_slots) => amm::instruction::EnableBatchMode { batch_slots }.data(),
+            None => amm::instruction::DisableBatchMode {}.data(),
+        };
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::Update { user: authority.pubkey(), config: self.config }.to_account_metas(None),
+            data,
+        };
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn slot(&mut self) -> u64 {
+        self.ctx.banks_client.get_sysvar::<Clock>().await.unwrap().slot
+    }
+
+    fn batch(&self, batch_id: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"batch", self.config.as_ref(), batch_id.to_le_bytes().as_ref()], &amm::ID).0
+    }
+
+    fn intent(&self, batch_id: u64, owner: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"intent", self.batch(batch_id).as_ref(), owner.as_ref()], &amm::ID).0
+    }
+
+    async fn commit_swap(&mut self, batch_id: u64, amount: u64, is_x: bool, min: u64) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let batch = self.batch(batch_id);
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::CommitSwap {
+                user: user.pubkey(),
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                config: self.config,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                batch,
+                intent: self.intent(batch_id, &user.pubkey()),
+                batch_vault_x: get_associated_token_address(&batch, &self.mint_x),
+                batch_vault_y: get_associated_token_address(&batch, &self.mint_y),
+                user_x: self.user_x,
+                user_y: self.user_y,
+                permit: self.permit,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::CommitSwap { batch_id, amount, is_x, min }.data(),
+        };
+        self.send(ix, &[&user]).await
+    }
+
+    // the test user opened every batch and owns its only intent
+    async fn settle_batch(&mut self, batch_id: u64) -> Result<(), BanksClientError> {
+        let payer = self.ctx.payer.insecure_clone();
+        let owner = self.user.pubkey();
+        let batch = self.batch(batch_id);
+        let mut accounts = amm::accounts::SettleBatch {
+            settler: payer.pubkey(),
+            payer: owner,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            config: self.config,
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            batch,
+            batch_vault_x: get_associated_token_address(&batch, &self.mint_x),
+            batch_vault_y: get_associated_token_address(&batch, &self.mint_y),
+            locker: self.locker,
+            locker_vault_x: self.locker_vault(self.mint_x),
+            locker_vault_y: self.locker_vault(self.mint_y),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend([
+            AccountMeta::new(self.intent(batch_id, &owner), false),
+            AccountMeta::new(owner, false),
+            AccountMeta::new(self.user_x, false),
+            AccountMeta::new(self.user_y, false),
+        ]);
+
+        let ix = Instruction { program_id: amm::ID, accounts, data: amm::instruction::SettleBatch {}.data() };
+        self.send(ix, &[&payer]).await
+    }
+
+    async fn refund_intent(&mut self, batch_id: u64) -> Result<(), BanksClientError> {
+        let payer = self.ctx.payer.insecure_clone();
+        let owner = self.user.pubkey();
+        let batch = self.batch(batch_id);
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::RefundIntent {
+                refunder: payer.pubkey(),
+                owner,
+                payer: owner,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                config: self.config,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                batch,
+                intent: self.intent(batch_id, &owner),
+                batch_vault_x: get_associated_token_address(&batch, &self.mint_x),
+                batch_vault_y: get_associated_token_address(&batch, &self.mint_y),
+                owner_x: self.user_x,
+                owner_y: self.user_y,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::RefundIntent {}.data(),
+        };
+        self.send(ix, &[&payer]).await
+    }
+
+    async fn set_dynamic_fee(&mut self, min_fee: u16, max_fee: u16, volatility_multiplier: u16, decay_period: i64) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::Update { user: authority.pubkey(), config: self.config }.to_account_metas(None),
+            data: amm::instruction::SetDynamicFee { min_fee, max_fee, volatility_multiplier, decay_period }.data(),
+        };
+        self.send(ix, &[&authority]).await
+    }
+
+    // moves the clock forward, everything that depends on unix_timestamp (farms, locks, the dynamic fee) sees the new time
+    async fn warp_time(&mut self, seconds: i64) {
+        let mut clock: Clock = self.ctx.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.ctx.set_sysvar(&clock);
+    }
+
+    fn farm(&self) -> (Pubkey, Pubkey) {
+        let farm = Pubkey::find_program_address(&[b"farm", self.config.as_ref()], &amm::ID).0;
+        (farm, Pubkey::find_program_address(&[b"position", farm.as_ref(), self.user.pubkey().as_ref()], &amm::ID).0)
+    }
+
+    async fn initialize_farm(&mut self, reward_mint: Pubkey) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let (farm, _) = self.farm();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::InitializeFarm {
+                authority: authority.pubkey(),
+                config: self.config,
+                mint_lp: self.mint_lp,
+                reward_mint,
+                farm,
+                farm_lp_vault: get_associated_token_address(&farm, &self.mint_lp),
+                reward_vault: get_associated_token_address(&farm, &reward_mint),
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::InitializeFarm {}.data(),
+        };
+        self.send(ix, &[&authority]).await
+    }
+
+    async fn fund_rewards(&mut self, reward_mint: Pubkey, amount: u64, duration: i64) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let (farm, _) = self.farm();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::FundRewards {
+                authority: authority.pubkey(),
+                config: self.config,
+                reward_mint,
+                farm,
+                authority_reward_ata: get_associated_token_address(&authority.pubkey(), &reward_mint),
+                reward_vault: get_associated_token_address(&farm, &reward_mint),
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::FundRewards { amount, duration }.data(),
+        };
+        self.send(ix, &[&authority]).await
+    }
+
+    // stakes amount lp tokens in the farm, or unstakes them when stake is false
+    async fn stake_lp(&mut self, amount: u64, stake: bool) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let (farm, position) = self.farm();
+        let farm_lp_vault = get_associated_token_address(&farm, &self.mint_lp);
+        let (accounts, data) = match stake {
+            true => (
+                amm::accounts::StakeLp {
+                    user: user.pubkey(),
+                    config: self.config,
+                    mint_lp: self.mint_lp,
+                    farm,
+                    position,
+                    user_lp: self.user_lp,
+                    farm_lp_vault,
+                    system_program: system_program::ID,
+                    token_program: spl_token::ID,
+                    associated_token_program: spl_associated_token_account::ID,
+                }
+                .to_account_metas(None),
+                amm::instruction::StakeLp { amount }.data(),
+            ),
+            false => (
+                amm::accounts::UnstakeLp {
+                    user: user.pubkey(),
+                    config: self.config,
+                    mint_lp: self.mint_lp,
+                    farm,
+                    position,
+                    user_lp: self.user_lp,
+                    farm_lp_vault,
+                    token_program: spl_token::ID,
+                    associated_token_program: spl_associated_token_account::ID,
+                }
+                .to_account_metas(None),
+                amm::instruction::UnstakeLp { amount }.data(),
+            ),
+        };
+        self.send(Instruction { program_id: amm::ID, accounts, data }, &[&user]).await
+    }
+
+    async fn claim_rewards(&mut self, reward_mint: Pubkey) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let (farm, position) = self.farm();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::ClaimRewards {
+                user: user.pubkey(),
+                config: self.config,
+                reward_mint,
+                farm,
+                position,
+                user_reward_ata: get_associated_token_address(&user.pubkey(), &reward_mint),
+                reward_vault: get_associated_token_address(&farm, &reward_mint),
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::ClaimRewards {}.data(),
+        };
+        self.send(ix, &[&user]).await
+    }
+
+    fn weighted_pool(&self) -> (Pubkey, Pubkey) {
+        let pool = Pubkey::find_program_address(&[b"weighted", WEIGHTED_SEED.to_le_bytes().as_ref()], &amm::ID).0;
+        (pool, Pubkey::find_program_address(&[b"lp", pool.as_ref()], &amm::ID).0)
+    }
+
+    // a weighted pool of mint_x and mint_y
+    async fn initialize_weighted_pool(&mut self, weights: Vec<u16>) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let (pool, mint_lp) = self.weighted_pool();
+        let mut accounts = amm::accounts::InitializeWeightedPool {
+            initializer: authority.pubkey(),
+            pool,
+            mint_lp,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+        }
+        .to_account_metas(None);
+        for mint in [self.mint_x, self.mint_y] {
+            accounts.push(AccountMeta::new_readonly(mint, false));
+            accounts.push(AccountMeta::new(get_associated_token_address(&pool, &mint), false));
+        }
+
+        let data = amm::instruction::InitializeWeightedPool { seed: WEIGHTED_SEED, fee: FEE, weights }.data();
+        self.send(Instruction { program_id: amm::ID, accounts, data }, &[&authority]).await
+    }
+
+    async fn join_weighted_pool(&mut self, lp_amount: u64, max_amounts: Vec<u64>) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let (pool, mint_lp) = self.weighted_pool();
+        let mut accounts = amm::accounts::WeightedLiquidity {
+            user: user.pubkey(),
+            pool,
+            mint_lp,
+            user_lp: get_associated_token_address(&user.pubkey(), &mint_lp),
+            locked_lp: get_associated_token_address(&pool, &mint_lp),
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.weighted_assets(&[self.mint_x, self.mint_y]));
+
+        let data = amm::instruction::JoinWeightedPool { lp_amount, max_amounts }.data();
+        self.send(Instruction { program_id: amm::ID, accounts, data }, &[&user]).await
+    }
+
+    // remaining accounts for both assets of the weighted pool, in the order the instructions expect them
+    fn weighted_assets(&self, assets: &[Pubkey]) -> Vec<AccountMeta> {
+        let (pool, _) = self.weighted_pool();
+        assets
+            .iter()
+            .flat_map(|mint| {
+                let user_ata = match *mint == self.mint_x {
+                    true => self.user_x,
+                    false => self.user_y,
+                };
+                [AccountMeta::new(get_associated_token_address(&pool, mint), false), AccountMeta::new(user_ata, false)]
+            })
+            .collect()
+    }
+
+    async fn exit_weighted_pool(&mut self, lp_amount: u64, min_amounts: Vec<u64>) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let (pool, mint_lp) = self.weighted_pool();
+        let mut accounts = amm::accounts::WeightedLiquidity {
+            user: user.pubkey(),
+            pool,
+            mint_lp,
+            user_lp: get_associated_token_address(&user.pubkey(), &mint_lp),
+            locked_lp: get_associated_token_address(&pool, &mint_lp),
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.weighted_assets(&[self.mint_x, self.mint_y]));
+
+        let data = amm::instruction::ExitWeightedPool { lp_amount, min_amounts }.data();
+        self.send(Instruction { program_id: amm::ID, accounts, data }, &[&user]).await
+    }
+
+    // sells x for y when is_x, like swap
+    async fn swap_weighted(&mut self, is_x: bool, amount: u64, min: u64) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let (pool, _) = self.weighted_pool();
+        let (index_in, index_out, assets) = match is_x {
+            true => (0, 1, [self.mint_x, self.mint_y]),
+            false => (1, 0, [self.mint_y, self.mint_x]),
+        };
+        let mut accounts = amm::accounts::WeightedSwap { user: user.pubkey(), pool, token_program: spl_token::ID }.to_account_metas(None);
+        accounts.extend(self.weighted_assets(&assets));
+
+        let data = amm::instruction::SwapWeighted { index_in, index_out, amount, min }.data();
+        self.send(Instruction { program_id: amm::ID, accounts, data }, &[&user]).await
+    }
+
+    fn order(&self, seed: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"order", self.config.as_ref(), self.user.pubkey().as_ref(), seed.to_le_bytes().as_ref()], &amm::ID).0
+    }
+
+    async fn place_order(&mut self, seed: u64, is_x: bool, amount: u64, limit_price: u64) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let order = self.order(seed);
+        let (mint_in, user_ata_in) = match is_x {
+            true => (self.mint_x, self.user_x),
+            false => (self.mint_y, self.user_y),
+        };
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::PlaceOrder {
+                user: user.pubkey(),
+                mint_in,
+                config: self.config,
+                order,
+                order_vault: get_associated_token_address(&order, &mint_in),
+                user_ata_in,
+                permit: self.permit,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::PlaceOrder { seed, is_x, amount, limit_price }.data(),
+        };
+        self.send(ix, &[&user]).await
+    }
+
+    async fn cancel_order(&mut self, seed: u64, is_x: bool) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let order = self.order(seed);
+        let (mint_in, user_ata_in) = match is_x {
+            true => (self.mint_x, self.user_x),
+            false => (self.mint_y, self.user_y),
+        };
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::CancelOrder {
+                user: user.pubkey(),
+                mint_in,
+                config: self.config,
+                order,
+                order_vault: get_associated_token_address(&order, &mint_in),
+                user_ata_in,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::CancelOrder {}.data(),
+        };
+        self.send(ix, &[&user]).await
+    }
+
+    fn locker_vault(&self, mint: Pubkey) -> Option<Pubkey> {
+        self.locker.map(|locker| get_associated_token_address(&locker, &mint))
+    }
+
+    async fn initialize_locker(&mut self) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let locker = Pubkey::find_program_address(&[b"locker", self.config.as_ref()], &amm::ID).0;
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::InitializeLocker {
+                authority: authority.pubkey(),
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp,
+                config: self.config,
+                locker,
+                locker_lp_vault: get_associated_token_address(&locker, &self.mint_lp),
+                locker_vault_x: get_associated_token_address(&locker, &self.mint_x),
+                locker_vault_y: get_associated_token_address(&locker, &self.mint_y),
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::InitializeLocker {}.data(),
+        };
+        self.send(ix, &[&authority]).await?;
+
+        self.locker = Some(locker);
+        Ok(())
+    }
+
+    async fn lock_liquidity(&mut self, seed: u64, amount: u64, duration: i64) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let locker = self.locker.expect("no locker initialized");
+        let position = self.locked_position(seed);
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::LockLiquidity {
+                user: user.pubkey(),
+                config: self.config,
+                mint_lp: self.mint_lp,
+                locker,
+                position,
+                user_lp: self.user_lp,
+                locker_lp_vault: get_associated_token_address(&locker, &self.mint_lp),
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::LockLiquidity { seed, amount, duration }.data(),
+        };
+        self.send(ix, &[&user]).await
+    }
+
+    fn locked_position(&self, seed: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"locked", self.config.as_ref(), self.user.pubkey().as_ref(), seed.to_le_bytes().as_ref()], &amm::ID).0
+    }
+
+    async fn claim_locked_fees(&mut self, seed: u64) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let locker = self.locker.expect("no locker initialized");
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::ClaimLockedFees {
+                user: user.pubkey(),
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                config: self.config,
+                locker,
+                position: self.locked_position(seed),
+                locker_vault_x: get_associated_token_address(&locker, &self.mint_x),
+                locker_vault_y: get_associated_token_address(&locker, &self.mint_y),
+                user_x: self.user_x,
+                user_y: self.user_y,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::ClaimLockedFees {}.data(),
+        };
+        self.send(ix, &[&user]).await
+    }
+
+    async fn unlock_liquidity(&mut self, seed: u64) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let locker = self.locker.expect("no locker initialized");
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::UnlockLiquidity {
+                user: user.pubkey(),
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp,
+                config: self.config,
+                locker,
+                position: self.locked_position(seed),
+                locker_lp_vault: get_associated_token_address(&locker, &self.mint_lp),
+                locker_vault_x: get_associated_token_address(&locker, &self.mint_x),
+                locker_vault_y: get_associated_token_address(&locker, &self.mint_y),
+                user_lp: self.user_lp,
+                user_x: self.user_x,
+                user_y: self.user_y,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: amm::instruction::UnlockLiquidity {}.data(),
+        };
+        self.send(ix, &[&user]).await
+    }
+
+    async fn set_boost_share(&mut self, boost_share: u16) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::Update { user: authority.pubkey(), config: self.config }.to_account_metas(None),
+            data: amm::instruction::SetBoostShare { boost_share }.data(),
+        };
+        self.send(ix, &[&authority]).await
+    }
+
+    // fills the user's orders with the given seeds, the test payer is the keeper
+    async fn fill_orders(&mut self, seeds: &[(u64, bool)]) -> Result<(), BanksClientError> {
+        let keeper = self.ctx.payer.insecure_clone();
+        let owner = self.user.pubkey();
+        let mut accounts = amm::accounts::FillOrders {
+            keeper: keeper.pubkey(),
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            mint_lp: self.mint_lp,
+            config: self.config,
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            keeper_x: get_associated_token_address(&keeper.pubkey(), &self.mint_x),
+            keeper_y: get_associated_token_address(&keeper.pubkey(), &self.mint_y),
+            locker: self.locker,
+            locker_vault_x: self.locker_vault(self.mint_x),
+            locker_vault_y: self.locker_vault(self.mint_y),
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        for (seed, is_x) in seeds {
+            let order = self.order(*seed);
+            let (mint_in, owner_ata_out) = match is_x {
+                true => (self.mint_x, self.user_y),
+                false => (self.mint_y, self.user_x),
+            };
+            accounts.extend([
+                AccountMeta::new(order, false),
+                AccountMeta::new(get_associated_token_address(&order, &mint_in), false),
+                AccountMeta::new(owner, false),
+                AccountMeta::new(owner_ata_out, false),
+            ]);
+        }
+
+        let ix = Instruction { program_id: amm::ID, accounts, data: amm::instruction::FillOrders {}.data() };
+        self.send(ix, &[&keeper]).await
+    }
+
+    async fn migrate_config(&mut self) -> Result<(), BanksClientError> {
+        let payer = self.ctx.payer.insecure_clone();
+        let ix = Instruction {
+            program_id: amm::ID,
+            accounts: amm::accounts::MigrateConfig { payer: payer.pubkey(), config: self.config, system_program: system_program::ID }
+                .to_account_metas(None),
+            data: amm::instruction::MigrateConfig { seed: SEED }.data(),
+        };
+        self.send(ix, &[&payer]).await
+    }
+
+    async fn balance(&mut self, token_account: Pubkey) -> u64 {
+        let account = self.ctx.banks_client.get_account(token_account).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    async fn config(&mut self) -> Config {
+        let account = self.ctx.banks_client.get_account(self.config).await.unwrap().unwrap();
+        Config::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn k(&mut self) -> u128 {
+        self.balance(self.vault_x).await as u128 * self.balance(self.vault_y).await as u128
+    }
+}
+
+fn assert_amm_error(result: Result<(), BanksClientError>, error: AmmError) {
+    let code = ERROR_CODE_OFFSET + error as u32;
+    match result.expect_err("instruction should have failed").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(actual)) => assert_eq!(actual, code),
+        other => panic!("expected custom error {code}, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn initialize_sets_up_the_pool() {
+    let mut pool = Pool::new().await;
+    let config = pool.config().await;
+
+    assert_eq!(config.seed, SEED);
+    assert_eq!(config.authority, Some(pool.authority.pubkey()));
+    assert_eq!(config.mint_x, pool.mint_x);
+    assert_eq!(config.mint_y, pool.mint_y);
+    assert_eq!(config.fee, FEE);
+    assert!(!config.locked);
+    assert_eq!(pool.balance(pool.vault_x).await, 0);
+    assert_eq!(pool.balance(pool.vault_y).await, 0);
+}
+
+#[tokio::test]
+async fn deposit_provides_liquidity() {
+    let mut pool = Pool::new().await;
+
+    // the first deposit sets the price
+    pool.deposit(1_000_000, 1_000_000_000, 500_000_000).await.unwrap();
+    assert_eq!(pool.balance(pool.vault_x).await, 1_000_000_000);
+    assert_eq!(pool.balance(pool.vault_y).await, 500_000_000);
+    assert_eq!(pool.balance(pool.user_lp).await, 1_000_000);
+
+    // later deposits are proportional to the reserves
+    pool.deposit(500_000, 500_000_000, 250_000_000).await.unwrap();
+    assert_eq!(pool.balance(pool.vault_x).await, 1_500_000_000);
+    assert_eq!(pool.balance(pool.vault_y).await, 750_000_000);
+    assert_eq!(pool.balance(pool.user_lp).await, 1_500_000);
+}
+
+#[tokio::test]
+async fn swap_trades_against_the_reserves() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+    let k = pool.k().await;
+
+    pool.swap(10_000_000, true, 9_000_000).await.unwrap();
+    assert_eq!(pool.balance(pool.user_x).await, USER_FUNDS - 1_000_000_000 - 10_000_000);
+    assert!(pool.balance(pool.user_y).await > USER_FUNDS - 1_000_000_000 + 9_000_000);
+    assert!(pool.k().await >= k);
+
+    let k = pool.k().await;
+    pool.swap(10_000_000, false, 9_000_000).await.unwrap();
+    assert!(pool.k().await >= k);
+}
+
+#[tokio::test]
+async fn withdraw_returns_liquidity() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 500_000_000).await.unwrap();
+
+    pool.withdraw(400_000, 400_000_000, 200_000_000).await.unwrap();
+    assert_eq!(pool.balance(pool.user_lp).await, 600_000);
+    assert_eq!(pool.balance(pool.vault_x).await, 600_000_000);
+    assert_eq!(pool.balance(pool.vault_y).await, 300_000_000);
+    assert_eq!(pool.balance(pool.user_x).await, USER_FUNDS - 600_000_000);
+    assert_eq!(pool.balance(pool.user_y).await, USER_FUNDS - 300_000_000);
+}
+
+#[tokio::test]
+async fn slippage_is_enforced() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+
+    // 10% of the reserves can never return 10% of the other side
+    let result = pool.swap(100_000_000, true, 100_000_000).await;
+    assert_amm_error(result, AmmError::SwapError);
+
+    let result = pool.deposit(1_000_000, 999_999_999, 1_000_000_000).await;
+    assert_amm_error(result, AmmError::SlippageExceeded);
+
+    let result = pool.withdraw(500_000, 500_000_001, 0).await;
+    assert_amm_error(result, AmmError::SlippageExceeded);
+}
+
+#[tokio::test]
+async fn locked_pool_rejects_trading() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+
+    let authority = pool.authority.insecure_clone();
+    pool.update(&authority, true).await.unwrap();
+    assert!(pool.config().await.locked);
+
+    assert_amm_error(pool.swap(1_000_000, true, 0).await, AmmError::PoolLocked);
+    assert_amm_error(pool.deposit(1_000, 1_000_000, 1_000_000).await, AmmError::PoolLocked);
+    assert_amm_error(pool.withdraw(1_000, 0, 0).await, AmmError::PoolLocked);
+    assert_amm_error(pool.place_order(0, true, 1_000_000, 1).await, AmmError::PoolLocked);
+    assert_amm_error(pool.update(&authority, true).await, AmmError::PoolLocked);
+
+    pool.update(&authority, false).await.unwrap();
+    assert!(!pool.config().await.locked);
+    pool.swap(1_000_000, true, 0).await.unwrap();
+    assert_amm_error(pool.update(&authority, false).await, AmmError::PoolUnlocked);
+}
+
+#[tokio::test]
+async fn only_the_authority_can_lock() {
+    let mut pool = Pool::new().await;
+
+    let user = pool.user.insecure_clone();
+    assert_amm_error(pool.update(&user, true).await, AmmError::InvalidAuthority);
+    assert!(!pool.config().await.locked);
+}
+
+#[tokio::test]
+async fn permissioned_pool_requires_a_permit() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+    pool.set_permissioned(true).await.unwrap();
+    assert!(pool.config().await.permissioned);
+
+    assert_amm_error(pool.swap(1_000_000, true, 0).await, AmmError::PermitRequired);
+    assert_amm_error(pool.deposit(1_000, 1_000_000, 1_000_000).await, AmmError::PermitRequired);
+
+    pool.issue_permit().await.unwrap();
+    pool.swap(1_000_000, true, 0).await.unwrap();
+    pool.deposit(1_000, 10_000_000, 10_000_000).await.unwrap();
+
+    // a revoked permit no longer exists, so it can not be passed anymore
+    pool.revoke_permit().await.unwrap();
+    let permit = pool.permit.take().unwrap();
+    assert!(pool.ctx.banks_client.get_account(permit).await.unwrap().is_none());
+    assert_amm_error(pool.swap(1_000_000, true, 0).await, AmmError::PermitRequired);
+
+    // liquidity providers can always exit
+    pool.withdraw(500_000, 0, 0).await.unwrap();
+
+    pool.set_permissioned(false).await.unwrap();
+    pool.swap(1_000_000, true, 0).await.unwrap();
+}
+
+#[tokio::test]
+async fn locked_pool_refunds_a_batch() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+    pool.set_batch_mode(Some(10)).await.unwrap();
+
+    let batch_id = pool.slot().await / 10;
+    pool.commit_swap(batch_id, 10_000_000, true, 0).await.unwrap();
+    assert_eq!(pool.balance(pool.user_x).await, USER_FUNDS - 1_000_000_000 - 10_000_000);
+
+    let authority = pool.authority.insecure_clone();
+    pool.update(&authority, true).await.unwrap();
+    pool.ctx.warp_to_slot((batch_id + 1) * 10).unwrap();
+    pool.settle_batch(batch_id).await.unwrap();
+
+    // nothing traded, the intent got its x back and the reserves did not move
+    assert_eq!(pool.balance(pool.user_x).await, USER_FUNDS - 1_000_000_000);
+    assert_eq!(pool.balance(pool.user_y).await, USER_FUNDS - 1_000_000_000);
+    assert_eq!(pool.balance(pool.vault_x).await, 1_000_000_000);
+    assert_eq!(pool.balance(pool.vault_y).await, 1_000_000_000);
+    assert!(pool.ctx.banks_client.get_account(pool.batch(batch_id)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn dust_intents_are_rejected() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+    pool.set_batch_mode(Some(10)).await.unwrap();
+
+    // MIN_INTENT_SHARE of a 1_000_000_000 reserve is 1_000_000
+    let batch_id = pool.slot().await / 10;
+    assert_amm_error(pool.commit_swap(batch_id, 999_999, true, 0).await, AmmError::IntentTooSmall);
+    pool.commit_swap(batch_id, 1_000_000, true, 0).await.unwrap();
+}
+
+#[tokio::test]
+async fn unsettled_batch_is_refunded() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+    pool.set_batch_mode(Some(10)).await.unwrap();
+
+    let batch_id = pool.slot().await / 10;
+    pool.commit_swap(batch_id, 10_000_000, false, 0).await.unwrap();
+
+    let settle_slot = (batch_id + 1) * 10;
+    pool.ctx.warp_to_slot(settle_slot).unwrap();
+    assert_amm_error(pool.refund_intent(batch_id).await, AmmError::BatchNotExpired);
+
+    pool.ctx.warp_to_slot(settle_slot + amm::BATCH_REFUND_SLOTS).unwrap();
+    pool.refund_intent(batch_id).await.unwrap();
+
+    assert_eq!(pool.balance(pool.user_y).await, USER_FUNDS - 1_000_000_000);
+    assert_eq!(pool.balance(pool.vault_y).await, 1_000_000_000);
+    assert!(pool.ctx.banks_client.get_account(pool.intent(batch_id, &pool.user.pubkey())).await.unwrap().is_none());
+    assert!(pool.ctx.banks_client.get_account(pool.batch(batch_id)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn first_weighted_join_mints_the_invariant() {
+    let mut pool = Pool::new().await;
+    pool.initialize_weighted_pool(vec![5_000, 5_000]).await.unwrap();
+    let (weighted, mint_lp) = pool.weighted_pool();
+    let user_lp = get_associated_token_address(&pool.user.pubkey(), &mint_lp);
+
+    // too little to cover the locked minimum
+    assert_amm_error(pool.join_weighted_pool(1, vec![100, 100]).await, AmmError::LiquidityLessThanMinimum);
+
+    // sqrt(1_000_000_000 * 4_000_000_000) is 2_000_000_000, the first join can not ask for more than that
+    assert_amm_error(pool.join_weighted_pool(2_000_000_000, vec![1_000_000_000, 4_000_000_000]).await, AmmError::SlippageExceeded);
+    pool.join_weighted_pool(1_999_000_000, vec![1_000_000_000, 4_000_000_000]).await.unwrap();
+
+    let locked = pool.balance(get_associated_token_address(&weighted, &mint_lp)).await;
+    let minted = pool.balance(user_lp).await;
+    assert_eq!(locked, amm::MINIMUM_LIQUIDITY);
+    assert!(minted + locked <= 2_000_000_000 && minted > 1_999_000_000, "{minted}");
+
+    // later joins pay in proportion to the lp they ask for
+    pool.join_weighted_pool(1_000_000, vec![1_000_000, 2_000_001]).await.unwrap();
+    assert_eq!(pool.balance(user_lp).await, minted + 1_000_000);
+}
+
+#[tokio::test]
+async fn batches_and_orders_pay_the_boosted_fee() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+    pool.initialize_locker().await.unwrap();
+    pool.lock_liquidity(0, 500_000, 3_600).await.unwrap();
+    pool.set_boost_share(5_000).await.unwrap();
+    let locker_vault_x = pool.locker_vault(pool.mint_x).unwrap();
+    let locker_vault_y = pool.locker_vault(pool.mint_y).unwrap();
+
+    // half of the 0.3% fee on 10_000_000 x
+    pool.place_order(0, true, 10_000_000, 1).await.unwrap();
+    pool.fill_orders(&[(0, true)]).await.unwrap();
+    assert_eq!(pool.balance(locker_vault_x).await, 15_000);
+
+    pool.set_batch_mode(Some(10)).await.unwrap();
+    let batch_id = pool.slot().await / 10;
+    pool.commit_swap(batch_id, 20_000_000, false, 0).await.unwrap();
+    pool.ctx.warp_to_slot((batch_id + 1) * 10).unwrap();
+    pool.settle_batch(batch_id).await.unwrap();
+    assert_eq!(pool.balance(locker_vault_y).await, 30_000);
+}
+
+#[tokio::test]
+async fn migrate_config_grows_an_old_config() {
+    let mut pool = Pool::new().await;
+    let config = pool.config().await;
+
+    // a config as the first deployed version wrote it, without any of the fields appended since
+    let mut data = Config::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&SEED.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(pool.authority.pubkey().as_ref());
+    data.extend_from_slice(pool.mint_x.as_ref());
+    data.extend_from_slice(pool.mint_y.as_ref());
+    data.extend_from_slice(&FEE.to_le_bytes());
+    data.extend_from_slice(&[0, config.config_bump, config.lp_bump]);
+
+    let rent = pool.ctx.banks_client.get_rent().await.unwrap();
+    let old = Account { lamports: rent.minimum_balance(data.len()), data, owner: amm::ID, executable: false, rent_epoch: 0 };
+    pool.ctx.set_account(&pool.config, &old.into());
+
+    let authority = pool.authority.insecure_clone();
+    assert!(pool.update(&authority, true).await.is_err());
+
+    pool.migrate_config().await.unwrap();
+    let migrated = pool.config().await;
+    assert_eq!(migrated.seed, SEED);
+    assert_eq!(migrated.authority, Some(authority.pubkey()));
+    assert_eq!(migrated.fee, FEE);
+    assert_eq!(migrated.lp_bump, config.lp_bump);
+    assert!(migrated.dynamic_fee.is_none() && migrated.batch_slots.is_none());
+    assert_eq!(migrated.boost_share, 0);
+    assert!(!migrated.permissioned);
+
+    pool.update(&authority, true).await.unwrap();
+    assert!(pool.config().await.locked);
+    assert_amm_error(pool.migrate_config().await, AmmError::AlreadyMigrated);
+}
+
+#[tokio::test]
+async fn dynamic_fee_rises_with_volatility_and_decays() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+    pool.set_dynamic_fee(10, 100, 10_000, 3_600).await.unwrap();
+
+    // a 10% swap moves the price by about 17%, far more than the 0.9% it takes to reach max_fee
+    pool.swap(100_000_000, true, 0).await.unwrap();
+    let dynamic_fee = pool.config().await.dynamic_fee.unwrap();
+    assert!(dynamic_fee.volatility > 1_500, "{}", dynamic_fee.volatility);
+    assert_eq!(dynamic_fee.fee(), 100);
+
+    // a full decay period later only the movement of the next, tiny swap is left
+    pool.warp_time(3_600).await;
+    pool.swap(1_000, false, 0).await.unwrap();
+    let dynamic_fee = pool.config().await.dynamic_fee.unwrap();
+    assert!(dynamic_fee.volatility < 10, "{}", dynamic_fee.volatility);
+    assert_eq!(dynamic_fee.fee(), 10);
+}
+
+#[tokio::test]
+async fn farm_pays_rewards_to_stakers() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+    let reward_mint = pool.create_mint().await;
+    let authority = pool.authority.pubkey();
+    pool.create_funded_ata(&authority, reward_mint, 3_600_000).await;
+    pool.initialize_farm(reward_mint).await.unwrap();
+
+    // staked before the farm is funded, so every emitted reward goes to this position
+    pool.stake_lp(1_000_000, true).await.unwrap();
+    assert_eq!(pool.balance(pool.user_lp).await, 0);
+    pool.fund_rewards(reward_mint, 3_600_000, 3_600).await.unwrap();
+
+    pool.warp_time(7_200).await;
+    pool.claim_rewards(reward_mint).await.unwrap();
+    let claimed = pool.balance(get_associated_token_address(&pool.user.pubkey(), &reward_mint)).await;
+    assert!((3_599_999..=3_600_000).contains(&claimed), "{claimed}");
+
+    // the period is over, there is nothing left to claim
+    assert_amm_error(pool.claim_rewards(reward_mint).await, AmmError::NoRewardsToClaim);
+
+    pool.stake_lp(1_000_000, false).await.unwrap();
+    assert_eq!(pool.balance(pool.user_lp).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn weighted_pool_swaps_and_exits() {
+    let mut pool = Pool::new().await;
+    pool.initialize_weighted_pool(vec![8_000, 2_000]).await.unwrap();
+    pool.join_weighted_pool(999_000_000, vec![1_000_000_000, 1_000_000_000]).await.unwrap();
+    let (weighted, mint_lp) = pool.weighted_pool();
+    let vault_x = get_associated_token_address(&weighted, &pool.mint_x);
+    let vault_y = get_associated_token_address(&weighted, &pool.mint_y);
+    let (x, y) = (pool.balance(vault_x).await, pool.balance(vault_y).await);
+
+    // the fee is taken from the input before it is priced on the curve
+    let fee = (10_000_000u64 * FEE as u64).div_ceil(10_000);
+    let expected = amm::weighted_math::swap_out(x, 8_000, y, 2_000, 10_000_000 - fee).unwrap();
+    assert_amm_error(pool.swap_weighted(true, 10_000_000, expected + 1).await, AmmError::SlippageExceeded);
+    pool.swap_weighted(true, 10_000_000, expected).await.unwrap();
+    assert_eq!(pool.balance(vault_x).await, x + 10_000_000);
+    assert_eq!(pool.balance(vault_y).await, y - expected);
+
+    // a proportional exit pays out the same share of both vaults
+    let (x, y) = (pool.balance(vault_x).await, pool.balance(vault_y).await);
+    let user_lp = get_associated_token_address(&pool.user.pubkey(), &mint_lp);
+    let lp = pool.balance(user_lp).await;
+    let supply = lp + amm::MINIMUM_LIQUIDITY;
+    pool.exit_weighted_pool(lp / 2, vec![0, 0]).await.unwrap();
+    assert_eq!(pool.balance(vault_x).await, x - (x as u128 * (lp / 2) as u128 / supply as u128) as u64);
+    assert_eq!(pool.balance(vault_y).await, y - (y as u128 * (lp / 2) as u128 / supply as u128) as u64);
+    assert_eq!(pool.balance(user_lp).await, lp - lp / 2);
+}
+
+#[tokio::test]
+async fn orders_wait_for_their_limit_and_can_be_cancelled() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+
+    // asks for twice the current price of x, so no keeper can fill it yet
+    let limit_price = 2 * amm::PRICE_PRECISION as u64;
+    pool.place_order(0, true, 10_000_000, limit_price).await.unwrap();
+    assert_eq!(pool.balance(pool.user_x).await, USER_FUNDS - 1_000_000_000 - 10_000_000);
+    assert_amm_error(pool.fill_orders(&[(0, true)]).await, AmmError::NoOrdersFilled);
+
+    pool.cancel_order(0, true).await.unwrap();
+    assert_eq!(pool.balance(pool.user_x).await, USER_FUNDS - 1_000_000_000);
+    assert!(pool.ctx.banks_client.get_account(pool.order(0)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn locked_liquidity_earns_fees_until_it_is_unlocked() {
+    let mut pool = Pool::new().await;
+    pool.deposit(1_000_000, 1_000_000_000, 1_000_000_000).await.unwrap();
+    pool.initialize_locker().await.unwrap();
+    pool.lock_liquidity(0, 500_000, 3_600).await.unwrap();
+    pool.set_boost_share(5_000).await.unwrap();
+
+    // half of the 0.3% fee on 10_000_000 x goes to the only locked position
+    pool.swap(10_000_000, true, 0).await.unwrap();
+    let user_x = pool.balance(pool.user_x).await;
+    pool.claim_locked_fees(0).await.unwrap();
+    assert_eq!(pool.balance(pool.user_x).await, user_x + 15_000);
+    assert_amm_error(pool.claim_locked_fees(0).await, AmmError::NoRewardsToClaim);
+
+    assert_amm_error(pool.unlock_liquidity(0).await, AmmError::LockNotExpired);
+    pool.warp_time(3_600).await;
+    pool.unlock_liquidity(0).await.unwrap();
+    assert_eq!(pool.balance(pool.user_lp).await, 1_000_000);
+    assert!(pool.ctx.banks_client.get_account(pool.locked_position(0)).await.unwrap().is_none());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn k_never_decreases(swaps in prop::collection::vec((any::<bool>(), 1_000u64..100_000_000), 1..12)) {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let mut pool = Pool::new().await;
+            pool.deposit(1_000_000, 1_000_000_000, 700_000_000).await.unwrap();
+
+            let mut k = pool.k().await;
+            for (is_x, amount) in swaps {
+                pool.swap(amount, is_x, 0).await.unwrap();
+
+                let k_after = pool.k().await;
+                assert!(k_after >= k, "k decreased from {k} to {k_after}");
+                k = k_after;
+            }
+        });
+    }
+}
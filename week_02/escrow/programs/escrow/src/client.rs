@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::{BookEntry, OfferBook, ID};
+
+// off chain helpers for reading the offer books, they take the account fetcher as a closure so any rpc client works
+
+pub fn book_address(mint_a: &Pubkey, mint_b: &Pubkey, page: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"book", mint_a.as_ref(), mint_b.as_ref(), page.to_le_bytes().as_ref()],
+        &ID,
+    )
+    .0
+}
+
+// walks the pages of the (mint_a, mint_b) book from 0 until the first missing one, and returns every open offer cheapest first
+// fetch returns the data of an account, or None when it does not exist
+pub fn open_offers<F>(mint_a: &Pubkey, mint_b: &Pubkey, mut fetch: F) -> Result<Vec<BookEntry>>
+where
+    F: FnMut(&Pubkey) -> Option<Vec<u8>>,
+{
+    let mut offers = Vec::new();
+    let mut page = 0;
+
+    while let Some(data) = fetch(&book_address(mint_a, mint_b, page)) {
+        let book = OfferBook::try_deserialize(&mut data.as_slice())?;
+        offers.extend(book.entries);
+        page += 1;
+    }
+
+    offers.sort_by_key(|entry| entry.price);
+    Ok(offers)
+}
@@ -2,3 +2,23 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+// legs per side of a basket offer, when the basket is taken a requested leg costs four accounts and an offered leg three
+pub const MAX_BASKET_MINTS: usize = 4;
+
+// milestones of a single escrow, they are stored inline in the Escrow account
+pub const MAX_MILESTONES: usize = 8;
+
+// highest fee the admin can set, in basis points of the mint_b leg
+#[constant]
+pub const MAX_FEE_BPS: u16 = 500;
+
+// escrows listed on one page of an offer book
+pub const MAX_BOOK_ENTRIES: usize = 32;
+
+// book prices are mint_b per mint_a in raw units, multiplied by this to keep the fraction
+#[constant]
+pub const PRICE_SCALE: u64 = 1_000_000_000;
+
+// owner of the Pyth v2 price accounts oracle offers are priced with
+pub const PYTH_ORACLE_PROGRAM: Pubkey = pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
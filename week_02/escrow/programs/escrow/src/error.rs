@@ -8,4 +8,82 @@ pub enum EscrowError {
     InsufficientFunds,
     #[msg("Maker vault is empty")]
     EmptyVault,
+    #[msg("Fill exceeds the remaining amount")]
+    FillExceedsRemaining,
+    #[msg("Invalid expiry")]
+    InvalidExpiry,
+    #[msg("Escrow has expired")]
+    Expired,
+    #[msg("Escrow has not expired yet")]
+    NotExpired,
+    #[msg("Signer is not the designated taker")]
+    UnauthorizedTaker,
+    #[msg("Invalid basket")]
+    InvalidBasket,
+    #[msg("Basket account does not match the stored legs")]
+    BasketAccountMismatch,
+    #[msg("Escrow vault changed since the counter offer was made")]
+    CounterOutdated,
+    #[msg("Payment escrows can not be traded or refunded")]
+    PaymentEscrow,
+    #[msg("Escrow is not arbitrated")]
+    NotArbitrated,
+    #[msg("Invalid escrow status")]
+    InvalidStatus,
+    #[msg("Signer is not a party of this escrow")]
+    UnauthorizedParty,
+    #[msg("Signer is not the arbiter")]
+    UnauthorizedArbiter,
+    #[msg("Invalid split")]
+    InvalidSplit,
+    #[msg("Invalid milestone")]
+    InvalidMilestone,
+    #[msg("Milestone already approved")]
+    MilestoneApproved,
+    #[msg("Notice period has not passed yet")]
+    NoticePeriodActive,
+    #[msg("Token account required for an SPL leg")]
+    TokenAccountRequired,
+    #[msg("Native SOL is not supported here")]
+    NativeNotSupported,
+    #[msg("Fee is above the cap")]
+    FeeTooHigh,
+    #[msg("Signer is not the admin")]
+    UnauthorizedAdmin,
+    #[msg("Making escrows is paused")]
+    Paused,
+    #[msg("Offer book page is full")]
+    BookPageFull,
+    #[msg("Offer book account doesn't match")]
+    BookAccountMismatch,
+    #[msg("Price feed is invalid or not trading")]
+    InvalidPriceFeed,
+    #[msg("Price feed is too old")]
+    StalePrice,
+    #[msg("Price feed doesn't match the offer")]
+    PriceFeedMismatch,
+    #[msg("Offer is priced by an oracle")]
+    OraclePriced,
+    #[msg("Mint doesn't match the offer")]
+    MintMismatch,
+    #[msg("NFT is not verified in the offer's collection")]
+    CollectionMismatch,
+    #[msg("Offer asks for any NFT of a collection")]
+    CollectionOffer,
+    #[msg("Escrow is not a stream")]
+    NotStream,
+    #[msg("Nothing vested to withdraw")]
+    NothingVested,
+    #[msg("Escrows do not form a ring")]
+    InvalidRing,
+    #[msg("Escrow is not hashlocked")]
+    NotHashlocked,
+    #[msg("Secret doesn't match the hashlock")]
+    InvalidSecret,
+    #[msg("Escrow already has the current layout")]
+    AlreadyMigrated,
+    #[msg("Offer terms moved past the taker's limit")]
+    SlippageExceeded,
+    #[msg("Price feed confidence is too wide")]
+    PriceTooUncertain,
 }
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+// emitted by update_offer with the terms the offer has from now on
+#[event]
+pub struct OfferUpdated {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub deposit: u64, // mint_a now in the vault
+    pub receive: u64, // mint_b asked for all of it
+    pub expires_at: Option<i64>,
+}
+
+// emitted by claim_htlc, the secret unlocks the matching leg on the other chain
+#[event]
+pub struct HtlcClaimed {
+    pub escrow: Pubkey,
+    pub counterparty: Pubkey,
+    pub hash: [u8; 32],
+    pub secret: [u8; 32],
+}
@@ -0,0 +1,225 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::{CounterOffer, Escrow, EscrowConfig, EscrowError, OfferBook};
+
+/*
+    accounts required:
+        - maker
+        - taker
+        - mint_a
+        - mint_b
+        - config
+        - treasury
+        - treasury_ata_b
+        - maker_ata_b
+        - taker_ata_a
+        - escrow
+        - vault
+        - counter
+        - counter_vault
+        - book (optional)
+        - other three accounts
+*/
+
+// the maker accepts a counter offer, both vaults are swapped and closed in one go
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct AcceptCounter<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(mut)]
+    pub taker: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, EscrowConfig>,
+
+    #[account(
+        address = config.treasury,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    // the fee on the countered amount lands here, the maker pays for it the first time
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = taker,
+    )]
+    pub taker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = taker,
+        has_one = escrow,
+        has_one = taker,
+        seeds = [b"counter", escrow.key().as_ref(), taker.key().as_ref()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, CounterOffer>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = counter,
+        associated_token::token_program = token_program,
+    )]
+    pub counter_vault: InterfaceAccount<'info, TokenAccount>,
+
+    // page of the pair's offer book the escrow is listed on, left out when the offer is not listed
+    #[account(
+        mut,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"book", mint_a.key().as_ref(), mint_b.key().as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump
+    )]
+    pub book: Option<Account<'info, OfferBook>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> AcceptCounter<'info> {
+    pub fn accept_counter(&mut self, _seed: u64) -> Result<()> {
+        // a partial fill in between changes what the taker would get, so the counter no longer applies
+        require!(self.vault.amount == self.counter.amount_a, EscrowError::CounterOutdated);
+
+        OfferBook::delist(self.book.as_mut(), &self.escrow)?;
+
+        /* Step 1: Transfer the escrowed mint_b from counter_vault to maker_ata_b less the protocol fee, and close counter_vault */
+        let escrow_key = self.escrow.key();
+        let taker_key = self.taker.key();
+        let counter_seeds: &[&[&[u8]]] = &[&[
+            b"counter",
+            escrow_key.as_ref(),
+            taker_key.as_ref(),
+            &[self.counter.bump]
+        ]];
+
+        let counter_cpi_accounts = TransferChecked {
+            from: self.counter_vault.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to: self.maker_ata_b.to_account_info(),
+            authority: self.counter.to_account_info(),
+        };
+
+        let counter_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), counter_cpi_accounts, counter_seeds);
+
+        let fee = self.config.fee_for(self.counter_vault.amount);
+        transfer_checked(counter_cpi_ctx, self.counter_vault.amount - fee, self.mint_b.decimals)?;
+
+        if fee > 0 {
+            let fee_cpi_accounts = TransferChecked {
+                from: self.counter_vault.to_account_info(),
+                mint: self.mint_b.to_account_info(),
+                to: self.treasury_ata_b.to_account_info(),
+                authority: self.counter.to_account_info(),
+            };
+
+            let fee_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), fee_cpi_accounts, counter_seeds);
+
+            transfer_checked(fee_cpi_ctx, fee, self.mint_b.decimals)?;
+        }
+
+        let close_accounts = CloseAccount {
+            account: self.counter_vault.to_account_info(),
+            destination: self.taker.to_account_info(),
+            authority: self.counter.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, counter_seeds);
+
+        close_account(close_cpi_ctx)?;
+
+        /* Step 2: Transfer the vault to taker_ata_a, and close the vault */
+        let binding_one = self.maker.key();
+        let binding_two = self.escrow.seed.to_le_bytes();
+
+        let escrow_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            binding_one.as_ref(),
+            binding_two.as_ref(),
+            &[self.escrow.bump]
+        ]];
+
+        let vault_cpi_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.taker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let vault_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), vault_cpi_accounts, escrow_seeds);
+
+        transfer_checked(vault_cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, escrow_seeds);
+
+        close_account(close_cpi_ctx)
+    }
+}
@@ -0,0 +1,122 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::Escrow;
+use crate::EscrowError;
+
+/*
+    accounts required:
+        - maker
+        - worker
+        - mint_a
+        - worker_ata_a
+        - escrow
+        - vault
+        - other three accounts
+*/
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct ApproveMilestone<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        constraint = escrow.taker == Some(worker.key()) @ EscrowError::UnauthorizedParty
+    )]
+    pub worker: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = worker,
+    )]
+    pub worker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_a,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ApproveMilestone<'info> {
+    // releases the tranche of milestone index to the worker, the last approval closes the vault and the escrow
+    pub fn approve_milestone(&mut self, _seed: u64, index: u8) -> Result<()> {
+        let milestone = self.escrow.milestones.get_mut(index as usize).ok_or(EscrowError::InvalidMilestone)?;
+        require!(!milestone.approved, EscrowError::MilestoneApproved);
+
+        milestone.approved = true;
+
+        // the last approval empties the vault, so nothing sent to it on top of the milestones stays locked
+        let last = self.escrow.milestones.iter().all(|milestone| milestone.approved);
+        let amount = match last {
+            true => self.vault.amount,
+            false => self.escrow.milestones[index as usize].amount,
+        };
+
+        let binding_one = self.maker.key();
+        let binding_two = self.escrow.seed.to_le_bytes();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            binding_one.as_ref(),
+            binding_two.as_ref(),
+            &[self.escrow.bump]
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.worker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, signer_seeds);
+
+        transfer_checked(transfer_cpi_ctx, amount, self.mint_a.decimals)?;
+
+        if !last {
+            return Ok(());
+        }
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, signer_seeds);
+
+        close_account(close_cpi_ctx)?;
+
+        self.escrow.close(self.maker.to_account_info())
+    }
+}
@@ -0,0 +1,106 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TransferChecked, Mint, TokenAccount, TokenInterface, transfer_checked, close_account}
+};
+
+use crate::Escrow;
+use crate::EscrowError;
+
+/*
+    accounts required:
+        - maker
+        - mint_a
+        - maker_ata_a
+        - escrow
+        - vault
+        - other three accounts
+*/
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+// same accounts as refund, the escrow is only closed once the notice period has passed
+pub struct CancelRemaining<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_a,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> CancelRemaining<'info> {
+    // the first call gives the worker notice, calling again after notice_period refunds every unapproved milestone
+    // the maker can still approve milestones while the notice runs
+    pub fn cancel_remaining(&mut self, _seed: u64) -> Result<()> {
+        require!(!self.escrow.milestones.is_empty(), EscrowError::InvalidMilestone);
+
+        let now = Clock::get()?.unix_timestamp;
+        let Some(cancel_notice_at) = self.escrow.cancel_notice_at else {
+            self.escrow.cancel_notice_at = Some(now);
+            return Ok(());
+        };
+        require!(now >= cancel_notice_at + self.escrow.notice_period, EscrowError::NoticePeriodActive);
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.maker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info()
+        };
+
+        let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, &signer_seeds);
+
+        transfer_checked(transfer_cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, &signer_seeds);
+
+        close_account(close_cpi_ctx)?;
+
+        self.escrow.close(self.maker.to_account_info())
+    }
+}
@@ -0,0 +1,136 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::Escrow;
+use crate::EscrowError;
+
+/*
+    accounts required:
+        - maker
+        - recipient
+        - mint_a
+        - maker_ata_a
+        - recipient_ata_a
+        - escrow
+        - vault
+        - other three accounts
+*/
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CancelStream<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        constraint = escrow.taker == Some(recipient.key()) @ EscrowError::UnauthorizedParty
+    )]
+    pub recipient: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = mint_a,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> CancelStream<'info> {
+    // the recipient keeps what has vested so far, the unvested rest goes back to the maker
+    pub fn cancel_stream(&mut self, _seed: u64) -> Result<()> {
+        let Some(stream) = self.escrow.stream else {
+            return err!(EscrowError::NotStream);
+        };
+
+        let vested = self.escrow.vested(Clock::get()?.unix_timestamp) - stream.withdrawn;
+        let unvested = self.vault.amount - vested;
+
+        let binding_one = self.maker.key();
+        let binding_two = self.escrow.seed.to_le_bytes();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            binding_one.as_ref(),
+            binding_two.as_ref(),
+            &[self.escrow.bump]
+        ]];
+
+        if vested > 0 {
+            let recipient_accounts = TransferChecked {
+                from: self.vault.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+                to: self.recipient_ata_a.to_account_info(),
+                authority: self.escrow.to_account_info(),
+            };
+
+            let recipient_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), recipient_accounts, signer_seeds);
+
+            transfer_checked(recipient_cpi_ctx, vested, self.mint_a.decimals)?;
+        }
+
+        if unvested > 0 {
+            let maker_accounts = TransferChecked {
+                from: self.vault.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+                to: self.maker_ata_a.to_account_info(),
+                authority: self.escrow.to_account_info(),
+            };
+
+            let maker_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), maker_accounts, signer_seeds);
+
+            transfer_checked(maker_cpi_ctx, unvested, self.mint_a.decimals)?;
+        }
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, signer_seeds);
+
+        close_account(close_cpi_ctx)
+    }
+}
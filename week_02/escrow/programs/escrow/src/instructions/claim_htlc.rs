@@ -0,0 +1,120 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::{Escrow, EscrowError, HtlcClaimed};
+
+/*
+    accounts required:
+        - counterparty
+        - maker
+        - mint_a
+        - counterparty_ata_a
+        - escrow
+        - vault
+        - other three accounts
+*/
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct ClaimHtlc<'info> {
+    #[account(mut)]
+    pub counterparty: Signer<'info>,
+
+    // receives the rent of the vault and the escrow
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = counterparty,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = counterparty,
+    )]
+    pub counterparty_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = mint_a,
+        constraint = escrow.taker == Some(counterparty.key()) @ EscrowError::UnauthorizedParty,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ClaimHtlc<'info> {
+    // the secret is a fixed 32 bytes, so a preimage accepted here is also accepted by a 32 byte hashlock on the other chain
+    pub fn claim_htlc(&mut self, _seed: u64, secret: [u8; 32]) -> Result<()> {
+        let Some(hashlock) = self.escrow.hashlock else {
+            return err!(EscrowError::NotHashlocked);
+        };
+        require!(Clock::get()?.unix_timestamp < hashlock.timeout, EscrowError::Expired);
+        require!(hash(&secret).to_bytes() == hashlock.hash, EscrowError::InvalidSecret);
+
+        let binding_one = self.maker.key();
+        let binding_two = self.escrow.seed.to_le_bytes();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            binding_one.as_ref(),
+            binding_two.as_ref(),
+            &[self.escrow.bump]
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.counterparty_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, signer_seeds);
+
+        transfer_checked(transfer_cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, signer_seeds);
+
+        close_account(close_cpi_ctx)?;
+
+        emit!(HtlcClaimed {
+            escrow: self.escrow.key(),
+            counterparty: self.counterparty.key(),
+            hash: hashlock.hash,
+            secret,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked}
+};
+
+use crate::{CounterOffer, Escrow, EscrowError};
+
+/*
+    accounts required:
+        - taker
+        - maker
+        - mint_a
+        - mint_b
+        - taker_ata_b
+        - escrow
+        - vault
+        - counter
+        - counter_vault
+        - other three accounts
+*/
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeCounterOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program,
+        associated_token::authority = taker,
+    )]
+    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    // one counter per taker and offer
+    #[account(
+        init,
+        payer = taker,
+        space = 8 + CounterOffer::INIT_SPACE,
+        seeds = [b"counter", escrow.key().as_ref(), taker.key().as_ref()],
+        bump
+    )]
+    pub counter: Account<'info, CounterOffer>,
+
+    #[account(
+        init,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = counter,
+        associated_token::token_program = token_program,
+    )]
+    pub counter_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> MakeCounterOffer<'info> {
+    // amount_b is the receive the taker proposes for the whole vault
+    pub fn counter_offer(&mut self, _seed: u64, amount_b: u64, bumps: &MakeCounterOfferBumps) -> Result<()> {
+        require!(self.escrow.is_swap(), EscrowError::PaymentEscrow);
+        require!(self.escrow.collection.is_none(), EscrowError::CollectionOffer);
+        require!(amount_b > 0, EscrowError::InvalidAmount);
+        require!(self.vault.amount > 0, EscrowError::EmptyVault);
+        require!(self.escrow.can_be_taken_by(&self.taker.key()), EscrowError::UnauthorizedTaker);
+        require!(!self.escrow.is_expired(Clock::get()?.unix_timestamp), EscrowError::Expired);
+        require!(self.taker_ata_b.amount >= amount_b, EscrowError::InsufficientFunds);
+
+        self.counter.set_inner(CounterOffer {
+            escrow: self.escrow.key(),
+            taker: self.taker.key(),
+            amount_a: self.vault.amount,
+            amount_b,
+            bump: bumps.counter,
+        });
+
+        let transfer_accounts = TransferChecked {
+            from: self.taker_ata_b.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to: self.counter_vault.to_account_info(),
+            authority: self.taker.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(ctx, amount_b, self.mint_b.decimals)
+    }
+}
@@ -0,0 +1,136 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::{Escrow, OfferBook};
+use crate::EscrowError;
+
+/*
+    accounts required:
+        - caller
+        - maker
+        - mint_a
+        - maker_ata_a
+        - escrow
+        - vault
+        - book (optional)
+        - other three accounts
+*/
+
+// permissionless cleanup of an expired escrow, anyone can call it and the caller earns the escrow's expire_bounty
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct Expire<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    // the maker may have closed this ata in the meantime, so the caller recreates it if needed
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_a: Option<InterfaceAccount<'info, TokenAccount>>, // not used when mint_a is native SOL
+
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_a,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>, // not used when mint_a is native SOL
+
+    // page of the pair's offer book the escrow is listed on, left out when the offer is not listed
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump
+    )]
+    pub book: Option<Account<'info, OfferBook>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> Expire<'info> {
+    pub fn expire_and_close_vault(&mut self, _seed: u64) -> Result<()> {
+        require!(self.escrow.is_swap(), EscrowError::PaymentEscrow);
+        require!(self.escrow.expires_at.is_some(), EscrowError::InvalidExpiry);
+        require!(self.escrow.is_expired(Clock::get()?.unix_timestamp), EscrowError::NotExpired);
+
+        OfferBook::delist(self.book.as_mut(), &self.escrow)?;
+
+        // a native deposit is part of the escrow's lamports, closing the escrow below returns it to the maker
+        if !self.escrow.is_native_a() {
+            self.refund_vault()?;
+        }
+
+        // the bounty comes out of the escrow rent, the rest of the rent goes back to the maker
+        let bounty = self.escrow.expire_bounty;
+        if bounty > 0 {
+            self.escrow.sub_lamports(bounty)?;
+            self.caller.add_lamports(bounty)?;
+        }
+
+        self.escrow.close(self.maker.to_account_info())
+    }
+
+    // empties the vault into the maker's ata and closes it, its rent goes back to the maker too
+    fn refund_vault(&self) -> Result<()> {
+        let vault = self.vault.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+        let maker_ata_a = self.maker_ata_a.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        // whatever is left after partial fills goes back to the maker
+        let transfer_accounts = TransferChecked {
+            from: vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: maker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info()
+        };
+
+        let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, &signer_seeds);
+
+        transfer_checked(transfer_cpi_ctx, vault.amount, self.mint_a.decimals)?;
+
+        let close_accounts = CloseAccount {
+            account: vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, &signer_seeds);
+
+        close_account(close_cpi_ctx)
+    }
+}
@@ -0,0 +1,56 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::prelude::*;
+
+use crate::{EscrowConfig, EscrowError, MAX_FEE_BPS};
+
+/*
+    accounts required:
+        - admin
+        - config
+        - program_data
+        - system program
+*/
+
+// creates the protocol config once, the signer becomes its admin
+// only the program's upgrade authority can do it, otherwise anyone could front-run the deployment and own the config
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + EscrowConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, EscrowConfig>,
+
+    // the loader keeps the upgrade authority in this account, at a pda of the program id
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = ProgramData::owner(),
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ EscrowError::UnauthorizedAdmin
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeConfig<'info> {
+    pub fn init_config(&mut self, fee_bps: u16, treasury: Pubkey, bumps: &InitializeConfigBumps) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, EscrowError::FeeTooHigh);
+
+        self.config.set_inner(EscrowConfig {
+            admin: self.admin.key(),
+            treasury,
+            fee_bps,
+            paused: false,
+            bump: bumps.config,
+        });
+
+        Ok(())
+    }
+}
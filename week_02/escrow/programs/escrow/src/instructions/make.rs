@@ -1,20 +1,23 @@
 #![allow(unexpected_cfgs)]
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer as system_transfer, Transfer as SystemTransfer};
 use anchor_spl::{
     associated_token::AssociatedToken, 
     token_interface::{TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked}
 };
 
-use crate::Escrow;
+use crate::{Escrow, EscrowConfig, EscrowError, EscrowStatus, Hashlock, Milestone, OfferBook, Stream, MAX_MILESTONES};
 
 /*
     accounts required:
         - maker
+        - config
         - mint_a
         - mint_b
         - maker_ata_a
         - escrow
         - vault
+        - book (optional)
         - other three accounts
 */
 
@@ -24,6 +27,14 @@ pub struct Make<'info> {
     #[account(mut)]
     pub maker: Signer<'info>,
 
+    // new escrows can not be made while the protocol is paused
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ EscrowError::Paused
+    )]
+    pub config: Account<'info, EscrowConfig>,
+
     #[account(
         mint::token_program = token_program,
     )]
@@ -40,7 +51,7 @@ pub struct Make<'info> {
         associated_token::token_program = token_program,
         associated_token::authority = maker,
     )]
-    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    pub maker_ata_a: Option<InterfaceAccount<'info, TokenAccount>>, // not used when mint_a is native SOL
 
     #[account(
         init,
@@ -60,7 +71,17 @@ pub struct Make<'info> {
         associated_token::authority = escrow,
         associated_token::token_program = token_program,
     )]
-    pub escrow_vault: InterfaceAccount<'info, TokenAccount>,
+    pub escrow_vault: Option<InterfaceAccount<'info, TokenAccount>>, // not used when mint_a is native SOL
+
+    // page of the pair's offer book the escrow is listed on, left out when the offer is not listed
+    #[account(
+        mut,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"book", mint_a.key().as_ref(), mint_b.key().as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump
+    )]
+    pub book: Option<Account<'info, OfferBook>>,
 
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -68,28 +89,257 @@ pub struct Make<'info> {
 }
 
 impl<'info> Make<'info> {
+    #[allow(clippy::too_many_arguments)]
     pub fn init_escrow_and_deposit(
         &mut self,
         seed: u64,
         deposit_amount: u64,
         receive: u64,
+        expires_at: Option<i64>,
+        expire_bounty: u64,
+        taker: Option<Pubkey>,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
+        // the bounty is paid out of the escrow rent, so it can not be larger than the rent and needs an expiry
+        if let Some(expires_at) = expires_at {
+            require!(expires_at > Clock::get()?.unix_timestamp, EscrowError::InvalidExpiry);
+        }
+        require!(expire_bounty == 0 || expires_at.is_some(), EscrowError::InvalidExpiry);
+        require!(
+            expire_bounty <= Rent::get()?.minimum_balance(8 + Escrow::INIT_SPACE),
+            EscrowError::InvalidAmount
+        );
+
+        self.escrow.set_inner(Escrow {
+            receive,
+            remaining: receive,
+            expires_at,
+            expire_bounty,
+            taker,
+            ..self.new_escrow(seed, deposit_amount, bumps)
+        });
+
+        // payment escrows are never listed, only swap offers
+        if let Some(book) = self.book.as_mut() {
+            book.list(self.escrow.key(), OfferBook::price_of(deposit_amount, receive))?;
+            self.escrow.book_page = Some(book.page);
+        }
+
+        self.deposit(deposit_amount)
+    }
+
+    // receive is left at 0, the taker pays the feed price of the deposit plus spread_bps when taking with transfer
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_oracle_escrow_and_deposit(
+        &mut self,
+        seed: u64,
+        deposit_amount: u64,
+        oracle: Pubkey,
+        spread_bps: u16,
+        max_price_age: Option<i64>,
+        max_conf_bps: Option<u16>,
+        expires_at: Option<i64>,
+        taker: Option<Pubkey>,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
+        if let Some(expires_at) = expires_at {
+            require!(expires_at > Clock::get()?.unix_timestamp, EscrowError::InvalidExpiry);
+        }
+        require!(max_price_age.is_none_or(|max_price_age| max_price_age >= 0), EscrowError::InvalidExpiry);
+        require!(max_conf_bps.is_none_or(|max_conf_bps| max_conf_bps <= 10_000), EscrowError::InvalidAmount);
+
+        self.escrow.set_inner(Escrow {
+            expires_at,
+            taker,
+            oracle: Some(oracle),
+            spread_bps,
+            max_price_age,
+            max_conf_bps,
+            ..self.new_escrow(seed, deposit_amount, bumps)
+        });
+
+        self.deposit(deposit_amount)
+    }
+
+    // mint_b is the collection mint, the offer is taken with transfer by paying one nft verified in that collection
+    pub fn init_collection_escrow_and_deposit(
+        &mut self,
+        seed: u64,
+        deposit_amount: u64,
+        expires_at: Option<i64>,
+        taker: Option<Pubkey>,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
+        if let Some(expires_at) = expires_at {
+            require!(expires_at > Clock::get()?.unix_timestamp, EscrowError::InvalidExpiry);
+        }
+
+        self.escrow.set_inner(Escrow {
+            receive: 1,
+            remaining: 1,
+            expires_at,
+            taker,
+            collection: Some(self.mint_b.key()),
+            ..self.new_escrow(seed, deposit_amount, bumps)
+        });
+
+        self.deposit(deposit_amount)
+    }
+
+    // the deposit vests linearly to recipient from start to end
+    pub fn init_stream_escrow_and_deposit(
+        &mut self,
+        seed: u64,
+        deposit_amount: u64,
+        recipient: Pubkey,
+        start: i64,
+        end: i64,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
+        require!(recipient != self.maker.key(), EscrowError::UnauthorizedParty);
+        require!(self.escrow_vault.is_some(), EscrowError::NativeNotSupported);
+        require!(end > start, EscrowError::InvalidExpiry);
+
+        self.escrow.set_inner(Escrow {
+            taker: Some(recipient),
+            stream: Some(Stream { start, end, withdrawn: 0 }),
+            ..self.new_escrow(seed, deposit_amount, bumps)
+        });
+
+        self.deposit(deposit_amount)
+    }
+
+    // the deposit is locked under hash, the counterparty claims it with the secret before timeout, after it only the maker can take it back
+    pub fn init_htlc_escrow_and_deposit(
+        &mut self,
+        seed: u64,
+        deposit_amount: u64,
+        counterparty: Pubkey,
+        hash: [u8; 32],
+        timeout: i64,
         bumps: &MakeBumps,
     ) -> Result<()> {
-        // Initialize the escrow account
+        require!(counterparty != self.maker.key(), EscrowError::UnauthorizedParty);
+        require!(self.escrow_vault.is_some(), EscrowError::NativeNotSupported);
+        require!(timeout > Clock::get()?.unix_timestamp, EscrowError::InvalidExpiry);
+
         self.escrow.set_inner(Escrow {
+            taker: Some(counterparty),
+            hashlock: Some(Hashlock { hash, timeout }),
+            ..self.new_escrow(seed, deposit_amount, bumps)
+        });
+
+        self.deposit(deposit_amount)
+    }
+
+    // the deposit is payable to counterparty, either when the maker releases it or as the arbiter rules in a dispute
+    pub fn init_arbitrated_escrow_and_deposit(
+        &mut self,
+        seed: u64,
+        deposit_amount: u64,
+        counterparty: Pubkey,
+        arbiter: Pubkey,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
+        require!(counterparty != self.maker.key(), EscrowError::UnauthorizedParty);
+        require!(self.escrow_vault.is_some(), EscrowError::NativeNotSupported);
+        require!(arbiter != self.maker.key() && arbiter != counterparty, EscrowError::UnauthorizedArbiter);
+
+        // nothing is asked in return, so the swap fields stay empty
+        self.escrow.set_inner(Escrow {
+            taker: Some(counterparty),
+            arbiter: Some(arbiter),
+            ..self.new_escrow(seed, deposit_amount, bumps)
+        });
+
+        self.deposit(deposit_amount)
+    }
+
+    // the sum of the milestones is deposited, and each one is paid to the worker once the maker approves it
+    pub fn init_milestone_escrow_and_deposit(
+        &mut self,
+        seed: u64,
+        milestones: Vec<Milestone>,
+        worker: Pubkey,
+        notice_period: i64,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
+        require!(worker != self.maker.key(), EscrowError::UnauthorizedParty);
+        require!(self.escrow_vault.is_some(), EscrowError::NativeNotSupported);
+        require!(!milestones.is_empty() && milestones.len() <= MAX_MILESTONES, EscrowError::InvalidMilestone);
+        require!(notice_period >= 0, EscrowError::InvalidExpiry);
+
+        let mut deposit_amount: u64 = 0;
+        for milestone in milestones.iter() {
+            require!(milestone.amount > 0, EscrowError::InvalidAmount);
+            require!(!milestone.approved, EscrowError::InvalidMilestone);
+            deposit_amount = deposit_amount.checked_add(milestone.amount).ok_or(EscrowError::InvalidAmount)?;
+        }
+
+        self.escrow.set_inner(Escrow {
+            taker: Some(worker),
+            milestones,
+            notice_period,
+            ..self.new_escrow(seed, deposit_amount, bumps)
+        });
+
+        self.deposit(deposit_amount)
+    }
+
+    // an active escrow of deposit_amount that asks nothing in return, every mode overrides the fields it uses
+    pub fn new_escrow(&self, seed: u64, deposit_amount: u64, bumps: &MakeBumps) -> Escrow {
+        Escrow {
             seed,
             maker: self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
-            receive,
+            receive: 0,
             bump: bumps.escrow,
-        });
+            deposit: deposit_amount,
+            filled: 0,
+            remaining: 0,
+            expires_at: None,
+            expire_bounty: 0,
+            taker: None,
+            arbiter: None,
+            status: EscrowStatus::Active,
+            milestones: Vec::new(),
+            notice_period: 0,
+            cancel_notice_at: None,
+            book_page: None,
+            oracle: None,
+            spread_bps: 0,
+            max_price_age: None,
+            collection: None,
+            stream: None,
+            hashlock: None,
+            max_conf_bps: None,
+        }
+    }
+
+    pub fn deposit(&mut self, deposit_amount: u64) -> Result<()> {
+        // native SOL is held by the escrow pda itself, on top of its rent
+        if self.escrow.is_native_a() {
+            require!(self.escrow_vault.is_none(), EscrowError::NativeNotSupported);
+
+            let system_accounts = SystemTransfer {
+                from: self.maker.to_account_info(),
+                to: self.escrow.to_account_info(),
+            };
+
+            let ctx = CpiContext::new(self.system_program.to_account_info(), system_accounts);
+
+            return system_transfer(ctx, deposit_amount);
+        }
+
+        let maker_ata_a = self.maker_ata_a.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+        let escrow_vault = self.escrow_vault.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
 
         // Transfer tokens from maker to escrow vault
         let transfer_accounts = TransferChecked {
-            from: self.maker_ata_a.to_account_info(),
+            from: maker_ata_a.to_account_info(),
             mint: self.mint_a.to_account_info(),
-            to: self.escrow_vault.to_account_info(),
+            to: escrow_vault.to_account_info(),
             authority: self.maker.to_account_info(),
         };
 
@@ -103,4 +353,3 @@ impl<'info> Make<'info> {
         Ok(())
     }
 }
-
@@ -0,0 +1,120 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{create, get_associated_token_address_with_program_id, AssociatedToken, Create},
+    token_interface::{Mint, TokenAccount, TokenInterface, transfer_checked, TransferChecked}
+};
+
+use crate::{BasketEscrow, BasketLeg, EscrowConfig, EscrowError};
+
+/*
+    accounts required:
+        - maker
+        - config
+        - basket
+        - other three accounts
+        - remaining accounts: for every offered leg [mint, maker_ata, vault]
+*/
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    // new escrows can not be made while the protocol is paused
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ EscrowError::Paused
+    )]
+    pub config: Account<'info, EscrowConfig>,
+
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + BasketEscrow::INIT_SPACE,
+        seeds = [b"basket", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> MakeBasket<'info> {
+    pub fn init_basket_and_deposit(
+        &mut self,
+        seed: u64,
+        offered: Vec<BasketLeg>,
+        requested: Vec<BasketLeg>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        bumps: &MakeBasketBumps,
+    ) -> Result<()> {
+        BasketEscrow::validate_legs(&offered)?;
+        BasketEscrow::validate_legs(&requested)?;
+        require!(remaining_accounts.len() == offered.len() * 3, EscrowError::BasketAccountMismatch);
+
+        for (leg, accounts) in offered.iter().zip(remaining_accounts.chunks_exact(3)) {
+            self.deposit_leg(leg, &accounts[0], &accounts[1], &accounts[2])?;
+        }
+
+        self.basket.set_inner(BasketEscrow {
+            seed,
+            maker: self.maker.key(),
+            offered,
+            requested,
+            bump: bumps.basket,
+        });
+
+        Ok(())
+    }
+
+    // creates the basket's vault for this leg and moves the offered amount into it
+    pub fn deposit_leg(
+        &self,
+        leg: &BasketLeg,
+        mint: &'info AccountInfo<'info>,
+        maker_ata: &'info AccountInfo<'info>,
+        vault: &'info AccountInfo<'info>,
+    ) -> Result<()> {
+        let token_program = self.token_program.key();
+        require!(mint.key() == leg.mint, EscrowError::BasketAccountMismatch);
+        require!(
+            maker_ata.key() == get_associated_token_address_with_program_id(&self.maker.key(), &leg.mint, &token_program),
+            EscrowError::BasketAccountMismatch
+        );
+        require!(
+            vault.key() == get_associated_token_address_with_program_id(&self.basket.key(), &leg.mint, &token_program),
+            EscrowError::BasketAccountMismatch
+        );
+
+        let mint_account: InterfaceAccount<'info, Mint> = InterfaceAccount::try_from(mint)?;
+        let maker_ata_account: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(maker_ata)?;
+        require!(maker_ata_account.amount >= leg.amount, EscrowError::InsufficientFunds);
+
+        let create_accounts = Create {
+            payer: self.maker.to_account_info(),
+            associated_token: vault.clone(),
+            authority: self.basket.to_account_info(),
+            mint: mint.clone(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+
+        create(CpiContext::new(self.associated_token_program.to_account_info(), create_accounts))?;
+
+        let transfer_accounts = TransferChecked {
+            from: maker_ata.clone(),
+            mint: mint.clone(),
+            to: vault.clone(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(ctx, leg.amount, mint_account.decimals)
+    }
+}
@@ -0,0 +1,88 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::{prelude::*, system_program::{transfer, Transfer}, Discriminator};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{Escrow, EscrowError};
+
+// this instruction grows an escrow made before partial fills, expiry and the other escrow modes existed to the current size
+/*
+    accounts required:
+        - payer
+        - maker
+        - mint_a
+        - escrow
+        - vault (optional)
+        - system_program, token_program
+*/
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MigrateEscrow<'info> {
+    // pays the rent of the new bytes, anyone can, the new fields are filled in from the escrow and its vault
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: only used for the escrow seeds
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: an old escrow is too short to deserialize as an Escrow, owner and discriminator are checked in migrate_escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: UncheckedAccount<'info>,
+
+    // every escrow of the first layout held mint_a in this vault, it is only read for their deposit
+    #[account(
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>, // not used for escrows that already track their deposit
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> MigrateEscrow<'info> {
+    pub fn migrate_escrow(&mut self, _seed: u64) -> Result<()> {
+        let escrow = self.escrow.to_account_info();
+        let space = 8 + Escrow::INIT_SPACE;
+
+        require_keys_eq!(*escrow.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+        require!(escrow.try_borrow_data()?.starts_with(Escrow::DISCRIMINATOR), ErrorCode::AccountDiscriminatorMismatch);
+        require!(escrow.data_len() < space, EscrowError::AlreadyMigrated);
+        let original = escrow.data_len() == 8 + Escrow::ORIGINAL_SPACE;
+
+        let missing_rent = Rent::get()?.minimum_balance(space).saturating_sub(escrow.lamports());
+        if missing_rent > 0 {
+            let transfer_accounts = Transfer {
+                from: self.payer.to_account_info(),
+                to: escrow.clone(),
+            };
+
+            transfer(CpiContext::new(self.system_program.to_account_info(), transfer_accounts), missing_rent)?;
+        }
+
+        // the old fields keep their place, the appended bytes are zeroed and read as switched off
+        escrow.resize(space)?;
+        if !original {
+            return Ok(());
+        }
+
+        // a first layout escrow could only be taken whole, so its vault is the deposit and nothing of receive is filled yet
+        let vault = self.vault.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+        let mut state = Escrow::try_deserialize(&mut &escrow.try_borrow_data()?[..])?;
+        require_keys_eq!(state.mint_a, self.mint_a.key(), EscrowError::MintMismatch);
+        state.deposit = vault.amount;
+        state.remaining = state.receive;
+
+        let mut data = escrow.try_borrow_mut_data()?;
+        state.try_serialize(&mut &mut data[..])
+    }
+}
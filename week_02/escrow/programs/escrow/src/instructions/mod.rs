@@ -5,4 +5,73 @@ pub mod refund;
 pub use refund::*;
 
 pub mod transfer;
-pub use transfer::*;
\ No newline at end of file
+pub use transfer::*;
+
+pub mod take_partial;
+pub use take_partial::*;
+
+pub mod expire;
+pub use expire::*;
+
+pub mod make_basket;
+pub use make_basket::*;
+
+pub mod take_basket;
+pub use take_basket::*;
+
+pub mod refund_basket;
+pub use refund_basket::*;
+
+pub mod counter_offer;
+pub use counter_offer::*;
+
+pub mod accept_counter;
+pub use accept_counter::*;
+
+pub mod withdraw_counter;
+pub use withdraw_counter::*;
+
+pub mod update_offer;
+pub use update_offer::*;
+
+pub mod release;
+pub use release::*;
+
+pub mod raise_dispute;
+pub use raise_dispute::*;
+
+pub mod resolve_dispute;
+pub use resolve_dispute::*;
+
+pub mod approve_milestone;
+pub use approve_milestone::*;
+
+pub mod cancel_remaining;
+pub use cancel_remaining::*;
+
+pub mod initialize_config;
+pub use initialize_config::*;
+
+pub mod update_config;
+pub use update_config::*;
+
+pub mod open_book_page;
+pub use open_book_page::*;
+
+pub mod withdraw_streamed;
+pub use withdraw_streamed::*;
+
+pub mod cancel_stream;
+pub use cancel_stream::*;
+
+pub mod settle_ring;
+pub use settle_ring::*;
+
+pub mod claim_htlc;
+pub use claim_htlc::*;
+
+pub mod reclaim_htlc;
+pub use reclaim_htlc::*;
+
+pub mod migrate_escrow;
+pub use migrate_escrow::*;
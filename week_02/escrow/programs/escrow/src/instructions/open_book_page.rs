@@ -0,0 +1,65 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{EscrowError, OfferBook};
+
+/*
+    accounts required:
+        - payer
+        - mint_a
+        - mint_b
+        - previous (the page before, left out for page 0)
+        - book
+        - system program
+*/
+
+// permissionless, whoever needs room in a full book opens the next page and pays its rent
+#[derive(Accounts)]
+#[instruction(page: u32)]
+pub struct OpenBookPage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"book", mint_a.key().as_ref(), mint_b.key().as_ref(), previous.page.to_le_bytes().as_ref()],
+        bump = previous.bump
+    )]
+    pub previous: Option<Account<'info, OfferBook>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + OfferBook::INIT_SPACE,
+        seeds = [b"book", mint_a.key().as_ref(), mint_b.key().as_ref(), page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub book: Account<'info, OfferBook>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenBookPage<'info> {
+    // pages have no gaps, so clients can walk them from 0 until the first missing one
+    pub fn open_book_page(&mut self, page: u32, bumps: &OpenBookPageBumps) -> Result<()> {
+        match &self.previous {
+            Some(previous) => require!(previous.page + 1 == page, EscrowError::BookAccountMismatch),
+            None => require!(page == 0, EscrowError::BookAccountMismatch),
+        }
+
+        self.book.set_inner(OfferBook {
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            page,
+            entries: Vec::new(),
+            bump: bumps.book,
+        });
+
+        Ok(())
+    }
+}
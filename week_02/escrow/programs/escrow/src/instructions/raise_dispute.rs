@@ -0,0 +1,45 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::prelude::*;
+
+use crate::{Escrow, EscrowError, EscrowStatus};
+
+/*
+    accounts required:
+        - party (maker or counterparty)
+        - maker
+        - escrow
+*/
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct RaiseDispute<'info> {
+    pub party: Signer<'info>,
+
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+impl<'info> RaiseDispute<'info> {
+    // once disputed, only the maker's release or the arbiter's ruling can pay out the vault
+    pub fn raise_dispute(&mut self, _seed: u64) -> Result<()> {
+        require!(self.escrow.is_arbitrated(), EscrowError::NotArbitrated);
+        require!(self.escrow.status == EscrowStatus::Active, EscrowError::InvalidStatus);
+
+        let party = self.party.key();
+        require!(
+            party == self.escrow.maker || Some(party) == self.escrow.taker,
+            EscrowError::UnauthorizedParty
+        );
+
+        self.escrow.status = EscrowStatus::Disputed;
+
+        Ok(())
+    }
+}
@@ -1,7 +1,8 @@
 #![allow(unexpected_cfgs)]
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token_interface::{CloseAccount, TransferChecked, Mint, TokenAccount, TokenInterface, transfer_checked, close_account}};
-use crate::Escrow;
+use crate::{Escrow, OfferBook};
+use crate::EscrowError;
 
 #[derive(Accounts)]
 #[instruction(seed:u64)]
@@ -26,7 +27,7 @@ pub struct Refund<'info>{
         associated_token::token_program = token_program,
         associated_token::authority = maker,
     )]
-    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    pub maker_ata_a: Option<InterfaceAccount<'info, TokenAccount>>, // not used when mint_a is native SOL
 
     #[account(
         mut,
@@ -44,7 +45,15 @@ pub struct Refund<'info>{
         associated_token::authority = escrow,
         associated_token::token_program = token_program,
     )]
-    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>, // not used when mint_a is native SOL
+
+    // page of the pair's offer book the escrow is listed on, left out when the offer is not listed
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump
+    )]
+    pub book: Option<Account<'info, OfferBook>>,
     
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -55,6 +64,19 @@ impl <'info> Refund <'info>{
 
     pub fn refund_and_close_vault(&mut self,_seed: u64)->Result<()>{
 
+        // arbitrated and milestone escrows are owed to the counterparty, the maker can not take them back alone
+        require!(self.escrow.is_swap(), EscrowError::PaymentEscrow);
+
+        OfferBook::delist(self.book.as_mut(), &self.escrow)?;
+
+        // a native deposit is part of the escrow's lamports, closing the escrow already returns it to the maker
+        if self.escrow.is_native_a() {
+            return Ok(());
+        }
+
+        let vault = self.vault.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+        let maker_ata_a = self.maker_ata_a.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+
         // we provide the seeds and the bump of the escrow in this section, so that the program can sign the transactions for the pda
         
         let signer_seeds: [&[&[u8]]; 1] = [&[
@@ -65,19 +87,19 @@ impl <'info> Refund <'info>{
         ]];
 
         let transfer_accounts = TransferChecked {
-            from: self.vault.to_account_info(),
+            from: vault.to_account_info(),
             mint: self.mint_a.to_account_info(),
-            to: self.maker_ata_a.to_account_info(),
+            to: maker_ata_a.to_account_info(),
             authority: self.escrow.to_account_info()
         };
 
         let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, &signer_seeds);
 
-        transfer_checked(transfer_cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+        transfer_checked(transfer_cpi_ctx, vault.amount, self.mint_a.decimals)?;
 
         // now close the vault
         let close_accounts = CloseAccount {
-            account: self.vault.to_account_info(),
+            account: vault.to_account_info(),
             destination: self.maker.to_account_info(),
             authority: self.escrow.to_account_info(),
         };
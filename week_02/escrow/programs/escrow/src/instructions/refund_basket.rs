@@ -0,0 +1,95 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::{BasketEscrow, EscrowError};
+
+/*
+    accounts required:
+        - maker
+        - basket
+        - other three accounts
+        - remaining accounts: for every offered leg [mint, maker_ata, vault]
+*/
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+// same as refund, but for every leg of a basket
+pub struct RefundBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = basket.bump
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> RefundBasket<'info> {
+    pub fn refund_basket(&mut self, _seed: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let offered = self.basket.offered.clone();
+        require!(remaining_accounts.len() == offered.len() * 3, EscrowError::BasketAccountMismatch);
+
+        let token_program = self.token_program.key();
+        let binding_one = self.maker.key();
+        let binding_two = self.basket.seed.to_le_bytes();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"basket",
+            binding_one.as_ref(),
+            binding_two.as_ref(),
+            &[self.basket.bump]
+        ]];
+
+        for (leg, accounts) in offered.iter().zip(remaining_accounts.chunks_exact(3)) {
+            let (mint, maker_ata, vault) = (&accounts[0], &accounts[1], &accounts[2]);
+
+            require!(mint.key() == leg.mint, EscrowError::BasketAccountMismatch);
+            require!(
+                maker_ata.key() == get_associated_token_address_with_program_id(&self.maker.key(), &leg.mint, &token_program),
+                EscrowError::BasketAccountMismatch
+            );
+            require!(
+                vault.key() == get_associated_token_address_with_program_id(&self.basket.key(), &leg.mint, &token_program),
+                EscrowError::BasketAccountMismatch
+            );
+
+            let mint_account: InterfaceAccount<'info, Mint> = InterfaceAccount::try_from(mint)?;
+            let vault_account: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(vault)?;
+
+            let transfer_accounts = TransferChecked {
+                from: vault.clone(),
+                mint: mint.clone(),
+                to: maker_ata.clone(),
+                authority: self.basket.to_account_info(),
+            };
+
+            let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, signer_seeds);
+
+            transfer_checked(transfer_cpi_ctx, vault_account.amount, mint_account.decimals)?;
+
+            let close_accounts = CloseAccount {
+                account: vault.clone(),
+                destination: self.maker.to_account_info(),
+                authority: self.basket.to_account_info(),
+            };
+
+            let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, signer_seeds);
+
+            close_account(close_cpi_ctx)?;
+        }
+
+        Ok(())
+    }
+}
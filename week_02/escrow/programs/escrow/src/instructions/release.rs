@@ -0,0 +1,105 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::Escrow;
+use crate::EscrowError;
+
+/*
+    accounts required:
+        - maker
+        - counterparty
+        - mint_a
+        - counterparty_ata_a
+        - escrow
+        - vault
+        - other three accounts
+*/
+
+// the maker approves an arbitrated escrow, the whole vault is paid to the counterparty
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct Release<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        constraint = escrow.taker == Some(counterparty.key()) @ EscrowError::UnauthorizedParty
+    )]
+    pub counterparty: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = counterparty,
+    )]
+    pub counterparty_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = mint_a,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> Release<'info> {
+    // the maker can also release during a dispute, which settles it in the counterparty's favor
+    pub fn release_and_close_vault(&mut self, _seed: u64) -> Result<()> {
+        require!(self.escrow.is_arbitrated(), EscrowError::NotArbitrated);
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.counterparty_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info()
+        };
+
+        let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, &signer_seeds);
+
+        transfer_checked(transfer_cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, &signer_seeds);
+
+        close_account(close_cpi_ctx)
+    }
+}
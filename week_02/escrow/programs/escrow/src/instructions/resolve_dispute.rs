@@ -0,0 +1,139 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::{Escrow, EscrowError, EscrowStatus};
+
+/*
+    accounts required:
+        - arbiter
+        - maker
+        - counterparty
+        - mint_a
+        - maker_ata_a
+        - counterparty_ata_a
+        - escrow
+        - vault
+        - other three accounts
+*/
+
+// the arbiter rules on a dispute by splitting the vault between counterparty and maker
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct ResolveDispute<'info> {
+    #[account(mut)]
+    pub arbiter: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        constraint = escrow.taker == Some(counterparty.key()) @ EscrowError::UnauthorizedParty
+    )]
+    pub counterparty: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = arbiter,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = arbiter,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = counterparty,
+    )]
+    pub counterparty_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = mint_a,
+        constraint = escrow.arbiter == Some(arbiter.key()) @ EscrowError::UnauthorizedArbiter,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ResolveDispute<'info> {
+    // counterparty_bps is the counterparty's share of the vault in basis points, the maker gets the rest including rounding
+    pub fn resolve_dispute(&mut self, _seed: u64, counterparty_bps: u16) -> Result<()> {
+        require!(self.escrow.status == EscrowStatus::Disputed, EscrowError::InvalidStatus);
+        require!(counterparty_bps <= 10_000, EscrowError::InvalidSplit);
+
+        let counterparty_amount = (self.vault.amount as u128 * counterparty_bps as u128 / 10_000) as u64;
+        let maker_amount = self.vault.amount - counterparty_amount;
+
+        self.pay(self.counterparty_ata_a.to_account_info(), counterparty_amount)?;
+        self.pay(self.maker_ata_a.to_account_info(), maker_amount)?;
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, &signer_seeds);
+
+        close_account(close_cpi_ctx)
+    }
+
+    pub fn pay(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to,
+            authority: self.escrow.to_account_info()
+        };
+
+        let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, &signer_seeds);
+
+        transfer_checked(transfer_cpi_ctx, amount, self.mint_a.decimals)
+    }
+}
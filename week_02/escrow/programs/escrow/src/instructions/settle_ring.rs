@@ -0,0 +1,191 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create},
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::{Escrow, EscrowConfig, EscrowError};
+
+/*
+    accounts required:
+        - settler
+        - config
+        - treasury
+        - other three accounts
+        - remaining accounts: for every escrow of the ring [escrow, maker, mint_a, vault, maker_ata_b, treasury_ata_a],
+          in ring order, so each escrow's mint_b is the next escrow's mint_a and the last one wraps to the first
+*/
+
+// permissionless, every maker of the ring gets the next escrow's whole vault for their own less the protocol fee, nobody brings outside funds
+#[derive(Accounts)]
+pub struct SettleRing<'info> {
+    // pays for the makers' and the treasury's atas that do not exist yet
+    #[account(mut)]
+    pub settler: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, EscrowConfig>,
+
+    #[account(
+        address = config.treasury,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub struct RingLeg<'info> {
+    pub escrow: Account<'info, Escrow>,
+    pub maker: &'info AccountInfo<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub maker_ata_b: &'info AccountInfo<'info>,
+    pub treasury_ata_a: &'info AccountInfo<'info>,
+}
+
+impl<'info> SettleRing<'info> {
+    pub fn settle_ring(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(
+            remaining_accounts.len() >= 2 * 6 && remaining_accounts.chunks_exact(6).remainder().is_empty(),
+            EscrowError::InvalidRing
+        );
+
+        let legs = remaining_accounts
+            .chunks_exact(6)
+            .map(|accounts| self.load_leg(accounts))
+            .collect::<Result<Vec<RingLeg<'info>>>>()?;
+
+        /* Step 1: check the whole ring before moving anything */
+        let now = Clock::get()?.unix_timestamp;
+        for (i, leg) in legs.iter().enumerate() {
+            let next = &legs[(i + 1) % legs.len()];
+            let previous = &legs[(i + legs.len() - 1) % legs.len()];
+
+            require!(legs[..i].iter().all(|other| other.escrow.key() != leg.escrow.key()), EscrowError::InvalidRing);
+            require!(leg.escrow.mint_b == next.escrow.mint_a, EscrowError::InvalidRing);
+            require!(next.vault.amount >= leg.escrow.remaining, EscrowError::InsufficientFunds);
+            require!(leg.escrow.can_be_taken_by(previous.maker.key), EscrowError::UnauthorizedTaker);
+            require!(!leg.escrow.is_expired(now), EscrowError::Expired);
+        }
+
+        /* Step 2: every vault goes to the maker before it in the ring, less the protocol fee that goes to the treasury, and is closed */
+        for (i, leg) in legs.iter().enumerate() {
+            let previous = &legs[(i + legs.len() - 1) % legs.len()];
+            self.release_leg(leg, previous)?;
+        }
+
+        /* Step 3: the escrows close last, once no more cpis need them */
+        for leg in legs.iter() {
+            leg.escrow.close(leg.maker.clone())?;
+        }
+
+        Ok(())
+    }
+
+    // only plain token swap offers can be part of a ring
+    pub fn load_leg(&self, accounts: &'info [AccountInfo<'info>]) -> Result<RingLeg<'info>> {
+        let token_program = self.token_program.key();
+        let escrow: Account<'info, Escrow> = Account::try_from(&accounts[0])?;
+
+        require!(escrow.is_swap(), EscrowError::PaymentEscrow);
+        require!(escrow.oracle.is_none(), EscrowError::OraclePriced);
+        require!(escrow.collection.is_none(), EscrowError::CollectionOffer);
+        require!(!escrow.is_native_a() && !escrow.is_native_b(), EscrowError::NativeNotSupported);
+        // a listed offer would leave a stale entry on its book page, it has to be settled with transfer
+        require!(escrow.book_page.is_none(), EscrowError::BookAccountMismatch);
+
+        require!(accounts[1].key() == escrow.maker, EscrowError::InvalidRing);
+        require!(accounts[2].key() == escrow.mint_a, EscrowError::InvalidRing);
+        require!(
+            accounts[3].key() == get_associated_token_address_with_program_id(&escrow.key(), &escrow.mint_a, &token_program),
+            EscrowError::InvalidRing
+        );
+        require!(
+            accounts[4].key() == get_associated_token_address_with_program_id(&escrow.maker, &escrow.mint_b, &token_program),
+            EscrowError::InvalidRing
+        );
+        require!(
+            accounts[5].key() == get_associated_token_address_with_program_id(&self.treasury.key(), &escrow.mint_a, &token_program),
+            EscrowError::InvalidRing
+        );
+
+        let vault: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(&accounts[3])?;
+        require!(vault.amount > 0, EscrowError::EmptyVault);
+
+        Ok(RingLeg {
+            escrow,
+            maker: &accounts[1],
+            mint_a: InterfaceAccount::try_from(&accounts[2])?,
+            vault,
+            maker_ata_b: &accounts[4],
+            treasury_ata_a: &accounts[5],
+        })
+    }
+
+    pub fn release_leg(&self, leg: &RingLeg<'info>, previous: &RingLeg<'info>) -> Result<()> {
+        // the previous maker asked for this escrow's mint_a, they may not hold it yet
+        self.create_ata_if_needed(previous.maker_ata_b, previous.maker.clone(), leg.mint_a.to_account_info())?;
+
+        let binding_one = leg.escrow.maker;
+        let binding_two = leg.escrow.seed.to_le_bytes();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            binding_one.as_ref(),
+            binding_two.as_ref(),
+            &[leg.escrow.bump]
+        ]];
+
+        // the previous maker pays for this vault like a taker would, so the fee is cut from it
+        let fee = self.config.fee_for(leg.vault.amount);
+        self.pay_from_vault(leg, previous.maker_ata_b.clone(), leg.vault.amount - fee, signer_seeds)?;
+
+        if fee > 0 {
+            self.create_ata_if_needed(leg.treasury_ata_a, self.treasury.to_account_info(), leg.mint_a.to_account_info())?;
+            self.pay_from_vault(leg, leg.treasury_ata_a.clone(), fee, signer_seeds)?;
+        }
+
+        let close_accounts = CloseAccount {
+            account: leg.vault.to_account_info(),
+            destination: leg.maker.clone(),
+            authority: leg.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, signer_seeds);
+
+        close_account(close_cpi_ctx)
+    }
+
+    pub fn pay_from_vault(&self, leg: &RingLeg<'info>, to: AccountInfo<'info>, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let transfer_accounts = TransferChecked {
+            from: leg.vault.to_account_info(),
+            mint: leg.mint_a.to_account_info(),
+            to,
+            authority: leg.escrow.to_account_info(),
+        };
+
+        let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, signer_seeds);
+
+        transfer_checked(transfer_cpi_ctx, amount, leg.mint_a.decimals)
+    }
+
+    pub fn create_ata_if_needed(&self, ata: &AccountInfo<'info>, authority: AccountInfo<'info>, mint: AccountInfo<'info>) -> Result<()> {
+        let create_accounts = Create {
+            payer: self.settler.to_account_info(),
+            associated_token: ata.clone(),
+            authority,
+            mint,
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+
+        create_idempotent(CpiContext::new(self.associated_token_program.to_account_info(), create_accounts))
+    }
+}
@@ -0,0 +1,212 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create},
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::{BasketEscrow, BasketLeg, EscrowConfig, EscrowError};
+
+/*
+    accounts required:
+        - maker
+        - taker
+        - config
+        - treasury
+        - basket
+        - other three accounts
+        - remaining accounts: for every requested leg [mint, taker_ata, maker_ata, treasury_ata],
+          then for every offered leg [mint, vault, taker_ata]
+*/
+
+// settles every leg of a basket atomically, if any leg fails the whole take fails
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct TakeBasket<'info> {
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, EscrowConfig>,
+
+    #[account(
+        address = config.treasury,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        seeds = [b"basket", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = basket.bump
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> TakeBasket<'info> {
+    pub fn take_basket(&mut self, _seed: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let requested = self.basket.requested.clone();
+        let offered = self.basket.offered.clone();
+        require!(
+            remaining_accounts.len() == requested.len() * 4 + offered.len() * 3,
+            EscrowError::BasketAccountMismatch
+        );
+
+        let (requested_accounts, offered_accounts) = remaining_accounts.split_at(requested.len() * 4);
+
+        /* Step 1: the taker pays every requested leg to the maker, less the protocol fee that goes to the treasury */
+        for (leg, accounts) in requested.iter().zip(requested_accounts.chunks_exact(4)) {
+            self.pay_requested_leg(leg, &accounts[0], &accounts[1], &accounts[2], &accounts[3])?;
+        }
+
+        /* Step 2: every vault goes to the taker and is closed */
+        for (leg, accounts) in offered.iter().zip(offered_accounts.chunks_exact(3)) {
+            self.release_offered_leg(leg, &accounts[0], &accounts[1], &accounts[2])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn pay_requested_leg(
+        &self,
+        leg: &BasketLeg,
+        mint: &'info AccountInfo<'info>,
+        taker_ata: &'info AccountInfo<'info>,
+        maker_ata: &'info AccountInfo<'info>,
+        treasury_ata: &'info AccountInfo<'info>,
+    ) -> Result<()> {
+        let token_program = self.token_program.key();
+        require!(mint.key() == leg.mint, EscrowError::BasketAccountMismatch);
+        require!(
+            taker_ata.key() == get_associated_token_address_with_program_id(&self.taker.key(), &leg.mint, &token_program),
+            EscrowError::BasketAccountMismatch
+        );
+        require!(
+            maker_ata.key() == get_associated_token_address_with_program_id(&self.maker.key(), &leg.mint, &token_program),
+            EscrowError::BasketAccountMismatch
+        );
+        require!(
+            treasury_ata.key() == get_associated_token_address_with_program_id(&self.treasury.key(), &leg.mint, &token_program),
+            EscrowError::BasketAccountMismatch
+        );
+
+        let mint_account: InterfaceAccount<'info, Mint> = InterfaceAccount::try_from(mint)?;
+        let taker_ata_account: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(taker_ata)?;
+        require!(taker_ata_account.amount >= leg.amount, EscrowError::InsufficientFunds);
+
+        // the maker may not hold this mint yet
+        self.create_ata_if_needed(maker_ata, self.maker.to_account_info(), mint)?;
+
+        let fee = self.config.fee_for(leg.amount);
+        self.pay_from_taker(taker_ata, maker_ata, mint, leg.amount - fee, mint_account.decimals)?;
+
+        if fee > 0 {
+            self.create_ata_if_needed(treasury_ata, self.treasury.to_account_info(), mint)?;
+            self.pay_from_taker(taker_ata, treasury_ata, mint, fee, mint_account.decimals)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn pay_from_taker(
+        &self,
+        taker_ata: &'info AccountInfo<'info>,
+        to: &'info AccountInfo<'info>,
+        mint: &'info AccountInfo<'info>,
+        amount: u64,
+        decimals: u8,
+    ) -> Result<()> {
+        let transfer_accounts = TransferChecked {
+            from: taker_ata.clone(),
+            mint: mint.clone(),
+            to: to.clone(),
+            authority: self.taker.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(ctx, amount, decimals)
+    }
+
+    pub fn release_offered_leg(
+        &self,
+        leg: &BasketLeg,
+        mint: &'info AccountInfo<'info>,
+        vault: &'info AccountInfo<'info>,
+        taker_ata: &'info AccountInfo<'info>,
+    ) -> Result<()> {
+        let token_program = self.token_program.key();
+        require!(mint.key() == leg.mint, EscrowError::BasketAccountMismatch);
+        require!(
+            vault.key() == get_associated_token_address_with_program_id(&self.basket.key(), &leg.mint, &token_program),
+            EscrowError::BasketAccountMismatch
+        );
+        require!(
+            taker_ata.key() == get_associated_token_address_with_program_id(&self.taker.key(), &leg.mint, &token_program),
+            EscrowError::BasketAccountMismatch
+        );
+
+        let mint_account: InterfaceAccount<'info, Mint> = InterfaceAccount::try_from(mint)?;
+        let vault_account: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(vault)?;
+        require!(vault_account.amount > 0, EscrowError::EmptyVault);
+
+        self.create_ata_if_needed(taker_ata, self.taker.to_account_info(), mint)?;
+
+        let binding_one = self.maker.key();
+        let binding_two = self.basket.seed.to_le_bytes();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"basket",
+            binding_one.as_ref(),
+            binding_two.as_ref(),
+            &[self.basket.bump]
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: vault.clone(),
+            mint: mint.clone(),
+            to: taker_ata.clone(),
+            authority: self.basket.to_account_info(),
+        };
+
+        let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, signer_seeds);
+
+        transfer_checked(transfer_cpi_ctx, vault_account.amount, mint_account.decimals)?;
+
+        let close_accounts = CloseAccount {
+            account: vault.clone(),
+            destination: self.maker.to_account_info(),
+            authority: self.basket.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, signer_seeds);
+
+        close_account(close_cpi_ctx)
+    }
+
+    // the taker pays for any ata that does not exist yet
+    pub fn create_ata_if_needed(&self, ata: &AccountInfo<'info>, authority: AccountInfo<'info>, mint: &AccountInfo<'info>) -> Result<()> {
+        let create_accounts = Create {
+            payer: self.taker.to_account_info(),
+            associated_token: ata.clone(),
+            authority,
+            mint: mint.clone(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+
+        create_idempotent(CpiContext::new(self.associated_token_program.to_account_info(), create_accounts))
+    }
+}
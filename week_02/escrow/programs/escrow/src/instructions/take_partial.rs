@@ -0,0 +1,230 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::{Escrow, EscrowConfig, OfferBook};
+use crate::EscrowError;
+
+/*
+    accounts required:
+        - maker
+        - taker
+        - mint_a
+        - mint_b
+        - config
+        - treasury
+        - treasury_ata_b
+        - maker_ata_b
+        - taker_ata_a
+        - taker_ata_b
+        - escrow
+        - vault
+        - book (optional)
+        - other three accounts
+*/
+
+// same accounts as transfer, except the escrow is only closed once the last piece is filled
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct TakePartial<'info> {
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, EscrowConfig>,
+
+    #[account(
+        address = config.treasury,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    // the fee on each piece lands here, the taker pays for it the first time
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = taker,
+    )]
+    pub taker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program,
+        associated_token::authority = taker,
+    )]
+    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    // page of the pair's offer book the escrow is listed on, left out when the offer is not listed
+    #[account(
+        mut,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"book", mint_a.key().as_ref(), mint_b.key().as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump
+    )]
+    pub book: Option<Account<'info, OfferBook>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> TakePartial<'info> {
+    // amount_b is the part of escrow.receive the taker pays now, mint_a is paid out pro rata
+    pub fn take_partial(&mut self, _seed: u64, amount_b: u64, min_amount_a: u64) -> Result<()> {
+        require!(self.escrow.is_swap(), EscrowError::PaymentEscrow);
+        require!(self.escrow.oracle.is_none(), EscrowError::OraclePriced);
+        require!(self.escrow.collection.is_none(), EscrowError::CollectionOffer);
+        require!(amount_b > 0, EscrowError::InvalidAmount);
+        require!(amount_b <= self.escrow.remaining, EscrowError::FillExceedsRemaining);
+        require!(self.vault.amount > 0, EscrowError::EmptyVault);
+        require!(self.escrow.can_be_taken_by(&self.taker.key()), EscrowError::UnauthorizedTaker);
+        require!(!self.escrow.is_expired(Clock::get()?.unix_timestamp), EscrowError::Expired);
+        require!(self.taker_ata_b.amount >= amount_b, EscrowError::InsufficientFunds);
+
+        // the last piece takes whatever is left in the vault, so no dust stays behind
+        let fully_filled = amount_b == self.escrow.remaining;
+        let amount_a = match fully_filled {
+            true => self.vault.amount,
+            false => self.escrow.payout_for(amount_b),
+        };
+        require!(amount_a > 0, EscrowError::InvalidAmount);
+        // a maker withdrawing from the vault in the meantime lowers the payout, the taker sets the least they accept
+        require!(amount_a >= min_amount_a, EscrowError::SlippageExceeded);
+
+        self.escrow.filled += amount_b;
+        self.escrow.remaining -= amount_b;
+
+        /* Step 1: Transfer tokens from taker_ata_b to maker_ata_b, less the protocol fee that goes to treasury_ata_b */
+        let fee = self.config.fee_for(amount_b);
+
+        let taker_cpi_accounts = TransferChecked {
+            from: self.taker_ata_b.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to: self.maker_ata_b.to_account_info(),
+            authority: self.taker.to_account_info(),
+        };
+
+        let taker_cpi_ctx = CpiContext::new(self.token_program.to_account_info(), taker_cpi_accounts);
+
+        transfer_checked(taker_cpi_ctx, amount_b - fee, self.mint_b.decimals)?;
+
+        if fee > 0 {
+            let fee_cpi_accounts = TransferChecked {
+                from: self.taker_ata_b.to_account_info(),
+                mint: self.mint_b.to_account_info(),
+                to: self.treasury_ata_b.to_account_info(),
+                authority: self.taker.to_account_info(),
+            };
+
+            let fee_cpi_ctx = CpiContext::new(self.token_program.to_account_info(), fee_cpi_accounts);
+
+            transfer_checked(fee_cpi_ctx, fee, self.mint_b.decimals)?;
+        }
+
+        /* Step 2: Transfer the pro rata share from vault to taker_ata_a */
+        let binding_one = self.maker.to_account_info().key();
+        let binding_two = self.escrow.seed.to_le_bytes();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            binding_one.as_ref(),
+            binding_two.as_ref(),
+            &[self.escrow.bump]
+        ]];
+
+        let vault_cpi_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.taker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let vault_cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            vault_cpi_accounts,
+            signer_seeds
+        );
+
+        transfer_checked(vault_cpi_ctx, amount_a, self.mint_a.decimals)?;
+
+        if !fully_filled {
+            return Ok(());
+        }
+
+        /* Step 3: Close the vault and the escrow once the offer is filled, a partly filled offer stays listed at the same price */
+        OfferBook::delist(self.book.as_mut(), &self.escrow)?;
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            signer_seeds
+        );
+
+        close_account(close_cpi_ctx)?;
+
+        self.escrow.close(self.maker.to_account_info())
+    }
+}
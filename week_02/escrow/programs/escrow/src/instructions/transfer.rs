@@ -1,12 +1,14 @@
 #![allow(unexpected_cfgs)]
 
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer as system_transfer, Transfer as SystemTransfer};
 use anchor_spl::{
     associated_token::AssociatedToken, 
+    metadata::{mpl_token_metadata, MasterEditionAccount, MetadataAccount},
     token_interface::{CloseAccount, TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked, close_account}
 };
 
-use crate::Escrow;
+use crate::{Escrow, EscrowConfig, OfferBook, PriceFeed};
 use crate::EscrowError;
 
 #[derive(Accounts)]
@@ -28,13 +30,37 @@ pub struct Transfer<'info> {
     )]
     pub mint_b: InterfaceAccount<'info, Mint>,
 
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, EscrowConfig>,
+
     #[account(
         mut,
+        address = config.treasury,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    // the fee in mint_b lands here, the taker pays for it the first time
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_ata_b: Option<InterfaceAccount<'info, TokenAccount>>, // not used when mint_b is native SOL
+
+    // a collection offer is paid with whichever nft the taker brings, so the taker creates the maker's ata for it
+    #[account(
+        init_if_needed,
+        payer = taker,
         associated_token::mint = mint_b,
         associated_token::token_program = token_program,
         associated_token::authority = maker,
     )]
-    pub maker_ata_b: InterfaceAccount<'info, TokenAccount>,
+    pub maker_ata_b: Option<InterfaceAccount<'info, TokenAccount>>, // not used when mint_b is native SOL
 
     #[account(
         mut,
@@ -42,7 +68,7 @@ pub struct Transfer<'info> {
         associated_token::token_program = token_program,
         associated_token::authority = taker,
     )]
-    pub taker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    pub taker_ata_a: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -50,14 +76,14 @@ pub struct Transfer<'info> {
         associated_token::token_program = token_program,
         associated_token::authority = taker,
     )]
-    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+    pub taker_ata_b: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         close = maker,
         has_one = maker,
         has_one = mint_a,
-        has_one = mint_b,
+        constraint = escrow.mint_b == mint_b.key() || escrow.collection.is_some() @ EscrowError::MintMismatch,
         seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
         bump = escrow.bump  // ✅ Fixed: specify bump = escrow.bump
     )]
@@ -70,7 +96,47 @@ pub struct Transfer<'info> {
         associated_token::authority = escrow,
         associated_token::token_program = token_program,
     )]
-    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>, // not used when mint_a is native SOL
+
+    // page of the pair's offer book the escrow is listed on, left out when the offer is not listed
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump
+    )]
+    pub book: Option<Account<'info, OfferBook>>,
+
+    /// CHECK: the feed the maker priced the offer with, its layout and owner are checked by PriceFeed::load
+    #[account(
+        constraint = escrow.oracle == Some(price_feed.key()) @ EscrowError::PriceFeedMismatch
+    )]
+    pub price_feed: Option<UncheckedAccount<'info>>, // only for oracle offers
+
+    // metadata and master edition of the taker's nft, only for collection offers
+    #[account(
+        seeds = [
+            b"metadata",
+            mpl_token_metadata::ID.as_ref(),
+            mint_b.key().as_ref(),
+        ],
+        seeds::program = mpl_token_metadata::ID,
+        bump,
+        constraint = metadata.collection.as_ref().is_some_and(|collection| collection.verified && Some(collection.key) == escrow.collection)
+            @ EscrowError::CollectionMismatch,
+    )]
+    pub metadata: Option<Account<'info, MetadataAccount>>,
+
+    #[account(
+        seeds = [
+            b"metadata",
+            mpl_token_metadata::ID.as_ref(),
+            mint_b.key().as_ref(),
+            b"edition"
+        ],
+        seeds::program = mpl_token_metadata::ID,
+        bump,
+    )]
+    pub master_edition: Option<Account<'info, MasterEditionAccount>>,
 
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -79,28 +145,145 @@ pub struct Transfer<'info> {
 
 impl<'info> Transfer<'info> {
     // ✅ Fixed: Added seed parameter to match instruction
-    pub fn transfer_and_close_vault(&mut self, seed: u64) -> Result<()> {
+    // max_receive is the most the taker pays, so a maker updating the offer in the meantime can not charge them more
+    pub fn transfer_and_close_vault(&mut self, seed: u64, max_receive: u64) -> Result<()> {
         require!(
-        self.vault.amount > 0, 
-        EscrowError::EmptyVault
+        self.escrow.is_swap(),
+        EscrowError::PaymentEscrow
+    );
+        require!(
+        self.escrow.can_be_taken_by(&self.taker.key()),
+        EscrowError::UnauthorizedTaker
+    );
+        require!(
+        !self.escrow.is_expired(Clock::get()?.unix_timestamp),
+        EscrowError::Expired
     );
+        // the metadata constraint has already checked the nft is verified in the collection, it only has to be there
         require!(
-        self.taker_ata_b.amount >= self.escrow.receive, 
+        self.escrow.collection.is_none() || (self.metadata.is_some() && self.master_edition.is_some()),
+        EscrowError::CollectionMismatch
+    );
+
+        OfferBook::delist(self.book.as_mut(), &self.escrow)?;
+
+        /* Step 1: Pay the maker, after partial fills only the remaining part of receive is still owed */
+        /* the protocol fee is cut from that payment, the taker still pays exactly what is owed */
+        let owed = match self.escrow.oracle {
+            Some(_) => self.oracle_receive()?,
+            None => self.escrow.remaining,
+        };
+        require!(owed <= max_receive, EscrowError::SlippageExceeded);
+        let fee = self.config.fee_for(owed);
+        self.pay_maker(owed - fee)?;
+        self.pay_fee(fee)?;
+
+        /* Step 2: Hand the deposit to the taker */
+        match self.escrow.is_native_a() {
+            true => self.release_native_deposit(),
+            false => self.release_vault(seed),
+        }
+    }
+
+    pub fn pay_maker(&mut self, amount: u64) -> Result<()> {
+        // native SOL goes straight from the taker's wallet to the maker
+        if self.escrow.is_native_b() {
+            require!(self.taker.lamports() >= amount, EscrowError::InsufficientFunds);
+
+            let system_accounts = SystemTransfer {
+                from: self.taker.to_account_info(),
+                to: self.maker.to_account_info(),
+            };
+
+            let system_cpi_ctx = CpiContext::new(self.system_program.to_account_info(), system_accounts);
+
+            return system_transfer(system_cpi_ctx, amount);
+        }
+
+        let taker_ata_b = self.taker_ata_b.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+        let maker_ata_b = self.maker_ata_b.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+        require!(
+        taker_ata_b.amount >= amount, 
         EscrowError::InsufficientFunds
     );
-        /* Step 1: Transfer tokens from taker_ata_b to maker_ata_b */
+
         let taker_cpi_accounts = TransferChecked {
-            from: self.taker_ata_b.to_account_info(),
+            from: taker_ata_b.to_account_info(),
             mint: self.mint_b.to_account_info(),
-            to: self.maker_ata_b.to_account_info(),
+            to: maker_ata_b.to_account_info(),
             authority: self.taker.to_account_info(),
         };
 
         let taker_cpi_ctx = CpiContext::new(self.token_program.to_account_info(), taker_cpi_accounts);
 
-        transfer_checked(taker_cpi_ctx, self.escrow.receive, self.mint_b.decimals)?;
-        
-        /* Step 2: Transfer tokens from vault to taker_ata_a */
+        transfer_checked(taker_cpi_ctx, amount, self.mint_b.decimals)
+    }
+
+    // the whole deposit priced at the feed, the feed has to be fresh and tight enough for the maker's max_price_age and max_conf_bps
+    pub fn oracle_receive(&self) -> Result<u64> {
+        let price_feed = self.price_feed.as_ref().ok_or(EscrowError::PriceFeedMismatch)?;
+        let feed = PriceFeed::load(price_feed)?;
+        let price = feed.usable_price(Clock::get()?.unix_timestamp, self.escrow.max_price_age, self.escrow.max_conf_bps)?;
+
+        self.escrow.oracle_receive(self.escrow.deposit, price, feed.expo, self.mint_a.decimals, self.mint_b.decimals)
+    }
+
+    pub fn pay_fee(&mut self, fee: u64) -> Result<()> {
+        if fee == 0 {
+            return Ok(());
+        }
+
+        if self.escrow.is_native_b() {
+            require!(self.taker.lamports() >= fee, EscrowError::InsufficientFunds);
+
+            let system_accounts = SystemTransfer {
+                from: self.taker.to_account_info(),
+                to: self.treasury.to_account_info(),
+            };
+
+            let system_cpi_ctx = CpiContext::new(self.system_program.to_account_info(), system_accounts);
+
+            return system_transfer(system_cpi_ctx, fee);
+        }
+
+        let taker_ata_b = self.taker_ata_b.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+        let treasury_ata_b = self.treasury_ata_b.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+
+        let fee_cpi_accounts = TransferChecked {
+            from: taker_ata_b.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to: treasury_ata_b.to_account_info(),
+            authority: self.taker.to_account_info(),
+        };
+
+        let fee_cpi_ctx = CpiContext::new(self.token_program.to_account_info(), fee_cpi_accounts);
+
+        transfer_checked(fee_cpi_ctx, fee, self.mint_b.decimals)
+    }
+
+    // the escrow pda holds native deposits as lamports on top of its rent, the rent goes back to the maker when it closes
+    pub fn release_native_deposit(&mut self) -> Result<()> {
+        require!(
+        self.escrow.deposit > 0, 
+        EscrowError::EmptyVault
+    );
+
+        let deposit = self.escrow.deposit;
+        self.escrow.sub_lamports(deposit)?;
+        self.taker.add_lamports(deposit)?;
+
+        Ok(())
+    }
+
+    pub fn release_vault(&mut self, _seed: u64) -> Result<()> {
+        let vault = self.vault.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+        let taker_ata_a = self.taker_ata_a.as_ref().ok_or(EscrowError::TokenAccountRequired)?;
+        require!(
+        vault.amount > 0, 
+        EscrowError::EmptyVault
+    );
+
+        /* Transfer tokens from vault to taker_ata_a */
         let binding_one = self.maker.to_account_info().key();
         let binding_two = self.escrow.seed.to_le_bytes();
        
@@ -112,9 +295,9 @@ impl<'info> Transfer<'info> {
         ]];
 
         let vault_cpi_accounts = TransferChecked {
-            from: self.vault.to_account_info(),
+            from: vault.to_account_info(),
             mint: self.mint_a.to_account_info(),
-            to: self.taker_ata_a.to_account_info(),
+            to: taker_ata_a.to_account_info(),
             authority: self.escrow.to_account_info(),
         };
 
@@ -125,11 +308,11 @@ impl<'info> Transfer<'info> {
         );
 
         // Transfer all tokens from vault to taker
-        transfer_checked(vault_cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+        transfer_checked(vault_cpi_ctx, vault.amount, self.mint_a.decimals)?;
 
-        /* Step 3: Close the vault account */
+        /* Close the vault account */
         let close_accounts = CloseAccount {
-            account: self.vault.to_account_info(),
+            account: vault.to_account_info(),
             destination: self.maker.to_account_info(),
             authority: self.escrow.to_account_info(),
         };
@@ -144,4 +327,4 @@ impl<'info> Transfer<'info> {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
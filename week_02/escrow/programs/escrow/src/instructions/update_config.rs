@@ -0,0 +1,50 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::prelude::*;
+
+use crate::{EscrowConfig, EscrowError, MAX_FEE_BPS};
+
+/*
+    accounts required:
+        - admin
+        - config
+*/
+
+// admin only settings of the escrow service
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ EscrowError::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, EscrowConfig>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    // applies to every take from now on, including escrows made before
+    pub fn set_fee(&mut self, fee_bps: u16) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, EscrowError::FeeTooHigh);
+
+        self.config.fee_bps = fee_bps;
+        Ok(())
+    }
+
+    pub fn set_treasury(&mut self, treasury: Pubkey) -> Result<()> {
+        self.config.treasury = treasury;
+        Ok(())
+    }
+
+    // pausing only stops new escrows, so makers and takers of existing ones are never locked in
+    pub fn set_paused(&mut self, paused: bool) -> Result<()> {
+        self.config.paused = paused;
+        Ok(())
+    }
+
+    pub fn set_admin(&mut self, admin: Pubkey) -> Result<()> {
+        self.config.admin = admin;
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked}
+};
+
+use crate::{Escrow, EscrowError, OfferBook, OfferUpdated};
+
+/*
+    accounts required:
+        - maker
+        - mint_a
+        - maker_ata_a
+        - escrow
+        - vault
+        - book (optional)
+        - other three accounts
+*/
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+// same accounts as refund, but the escrow and vault stay open
+pub struct UpdateOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_a,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    // page of the pair's offer book the escrow is listed on, left out when the offer is not listed
+    #[account(
+        mut,
+        seeds = [b"book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref(), book.page.to_le_bytes().as_ref()],
+        bump = book.bump
+    )]
+    pub book: Option<Account<'info, OfferBook>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> UpdateOffer<'info> {
+    // receive is the new price for everything left in the vault after top_up and withdraw, None keeps asking for escrow.remaining
+    // expires_at replaces the expiry, None keeps the current one
+    pub fn update_offer(
+        &mut self,
+        _seed: u64,
+        receive: Option<u64>,
+        top_up: u64,
+        withdraw: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        require!(self.escrow.is_swap(), EscrowError::PaymentEscrow);
+        require!(self.escrow.oracle.is_none(), EscrowError::OraclePriced);
+        require!(self.escrow.collection.is_none(), EscrowError::CollectionOffer);
+
+        let receive = receive.unwrap_or(self.escrow.remaining);
+        require!(receive > 0, EscrowError::InvalidAmount);
+        require!(withdraw <= self.vault.amount, EscrowError::InsufficientFunds);
+        require!(self.maker_ata_a.amount >= top_up, EscrowError::InsufficientFunds);
+
+        if let Some(expires_at) = expires_at {
+            require!(expires_at > Clock::get()?.unix_timestamp, EscrowError::InvalidExpiry);
+            self.escrow.expires_at = Some(expires_at);
+        }
+
+        if top_up > 0 {
+            self.top_up(top_up)?;
+        }
+        if withdraw > 0 {
+            self.withdraw(withdraw)?;
+        }
+
+        // an empty offer should be refunded instead
+        self.vault.reload()?;
+        require!(self.vault.amount > 0, EscrowError::EmptyVault);
+
+        // the fill ratio starts over from what is left
+        self.escrow.deposit = self.vault.amount;
+        self.escrow.receive = receive;
+        self.escrow.filled = 0;
+        self.escrow.remaining = receive;
+        OfferBook::reprice(self.book.as_mut(), &self.escrow)?;
+
+        emit!(OfferUpdated {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            deposit: self.escrow.deposit,
+            receive: self.escrow.receive,
+            expires_at: self.escrow.expires_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn top_up(&mut self, amount: u64) -> Result<()> {
+        let transfer_accounts = TransferChecked {
+            from: self.maker_ata_a.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(ctx, amount, self.mint_a.decimals)
+    }
+
+    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.maker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info()
+        };
+
+        let ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, &signer_seeds);
+
+        transfer_checked(ctx, amount, self.mint_a.decimals)
+    }
+}
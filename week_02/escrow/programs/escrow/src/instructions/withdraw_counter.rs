@@ -0,0 +1,93 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::CounterOffer;
+
+/*
+    accounts required:
+        - taker
+        - mint_b
+        - taker_ata_b
+        - counter
+        - counter_vault
+        - other three accounts
+*/
+
+// the taker backs out of a counter offer, this works even after the escrow itself is gone
+#[derive(Accounts)]
+pub struct WithdrawCounter<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program,
+        associated_token::authority = taker,
+    )]
+    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = taker,
+        has_one = taker,
+        seeds = [b"counter", counter.escrow.as_ref(), taker.key().as_ref()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, CounterOffer>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = counter,
+        associated_token::token_program = token_program,
+    )]
+    pub counter_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> WithdrawCounter<'info> {
+    pub fn withdraw_counter(&mut self) -> Result<()> {
+        let escrow_key = self.counter.escrow;
+        let taker_key = self.taker.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"counter",
+            escrow_key.as_ref(),
+            taker_key.as_ref(),
+            &[self.counter.bump]
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.counter_vault.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to: self.taker_ata_b.to_account_info(),
+            authority: self.counter.to_account_info(),
+        };
+
+        let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, signer_seeds);
+
+        transfer_checked(transfer_cpi_ctx, self.counter_vault.amount, self.mint_b.decimals)?;
+
+        let close_accounts = CloseAccount {
+            account: self.counter_vault.to_account_info(),
+            destination: self.taker.to_account_info(),
+            authority: self.counter.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, signer_seeds);
+
+        close_account(close_cpi_ctx)
+    }
+}
@@ -0,0 +1,127 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenAccount, Mint, TokenInterface, transfer_checked, TransferChecked, close_account}
+};
+
+use crate::Escrow;
+use crate::EscrowError;
+
+/*
+    accounts required:
+        - recipient
+        - maker
+        - mint_a
+        - recipient_ata_a
+        - escrow
+        - vault
+        - other three accounts
+*/
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct WithdrawStreamed<'info> {
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    // receives the rent once the stream is fully withdrawn
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = recipient,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_a,
+        constraint = escrow.taker == Some(recipient.key()) @ EscrowError::UnauthorizedParty,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> WithdrawStreamed<'info> {
+    // claims everything vested so far, the claim after end empties the vault and closes the escrow
+    pub fn withdraw_streamed(&mut self, _seed: u64) -> Result<()> {
+        let Some(mut stream) = self.escrow.stream else {
+            return err!(EscrowError::NotStream);
+        };
+
+        let claimable = self.escrow.vested(Clock::get()?.unix_timestamp) - stream.withdrawn;
+        require!(claimable > 0, EscrowError::NothingVested);
+
+        stream.withdrawn += claimable;
+        self.escrow.stream = Some(stream);
+
+        // the last claim takes the whole vault, so tokens sent to it on top of the deposit can not keep it from closing
+        let fully_withdrawn = stream.withdrawn >= self.escrow.deposit;
+        let amount = match fully_withdrawn {
+            true => self.vault.amount,
+            false => claimable,
+        };
+
+        let binding_one = self.maker.key();
+        let binding_two = self.escrow.seed.to_le_bytes();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            binding_one.as_ref(),
+            binding_two.as_ref(),
+            &[self.escrow.bump]
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.recipient_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let transfer_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), transfer_accounts, signer_seeds);
+
+        transfer_checked(transfer_cpi_ctx, amount, self.mint_a.decimals)?;
+
+        if !fully_withdrawn {
+            return Ok(());
+        }
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let close_cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), close_accounts, signer_seeds);
+
+        close_account(close_cpi_ctx)?;
+
+        self.escrow.close(self.maker.to_account_info())
+    }
+}
@@ -1,7 +1,11 @@
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
+// off chain only, none of it is needed by the deployed program
+#[cfg(not(target_os = "solana"))]
+pub mod client;
 
 use anchor_lang::prelude::*;
 
@@ -9,6 +13,7 @@ pub use constants::*;
 pub use instructions::*;
 pub use state::*;
 pub use error::*;
+pub use events::*;
 
 declare_id!("FZDK4T9zznUeC3xVDq8qZqmd1WerJvYmGwWPtXBuRgv6");
 
@@ -16,26 +21,222 @@ declare_id!("FZDK4T9zznUeC3xVDq8qZqmd1WerJvYmGwWPtXBuRgv6");
 pub mod escrow {
     use super::*;
 
+    pub fn initialize_config(ctx: Context<InitializeConfig>, fee_bps: u16, treasury: Pubkey) -> Result<()> {
+        ctx.accounts.init_config(fee_bps, treasury, &ctx.bumps)
+    }
+
+    pub fn set_fee(ctx: Context<UpdateConfig>, fee_bps: u16) -> Result<()> {
+        ctx.accounts.set_fee(fee_bps)
+    }
+
+    pub fn set_treasury(ctx: Context<UpdateConfig>, treasury: Pubkey) -> Result<()> {
+        ctx.accounts.set_treasury(treasury)
+    }
+
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.set_paused(paused)
+    }
+
+    pub fn set_admin(ctx: Context<UpdateConfig>, admin: Pubkey) -> Result<()> {
+        ctx.accounts.set_admin(admin)
+    }
+
+    pub fn open_book_page(ctx: Context<OpenBookPage>, page: u32) -> Result<()> {
+        ctx.accounts.open_book_page(page, &ctx.bumps)
+    }
+
     pub fn make(
         ctx: Context<Make>,
         seed: u64,
         deposit_amount: u64,
-        receive: u64,) 
+        receive: u64,
+        expires_at: Option<i64>,
+        expire_bounty: u64,
+        taker: Option<Pubkey>,)
         -> Result<()> {
         
         require!(deposit_amount > 0, EscrowError::InvalidAmount);
         require!(receive > 0, EscrowError::InvalidAmount);
 
-        ctx.accounts.init_escrow_and_deposit(seed, deposit_amount, receive, &ctx.bumps)
+        ctx.accounts.init_escrow_and_deposit(seed, deposit_amount, receive, expires_at, expire_bounty, taker, &ctx.bumps)
         
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_oracle(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit_amount: u64,
+        oracle: Pubkey,
+        spread_bps: u16,
+        max_price_age: Option<i64>,
+        max_conf_bps: Option<u16>,
+        expires_at: Option<i64>,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        require!(deposit_amount > 0, EscrowError::InvalidAmount);
+
+        ctx.accounts.init_oracle_escrow_and_deposit(seed, deposit_amount, oracle, spread_bps, max_price_age, max_conf_bps, expires_at, taker, &ctx.bumps)
+    }
+
+    pub fn make_collection(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit_amount: u64,
+        expires_at: Option<i64>,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        require!(deposit_amount > 0, EscrowError::InvalidAmount);
+
+        ctx.accounts.init_collection_escrow_and_deposit(seed, deposit_amount, expires_at, taker, &ctx.bumps)
+    }
+
+    pub fn migrate_escrow(ctx: Context<MigrateEscrow>, seed: u64) -> Result<()> {
+        ctx.accounts.migrate_escrow(seed)
+    }
+
     pub fn refund(ctx: Context<Refund>,seed: u64)->Result<()>{
         ctx.accounts.refund_and_close_vault(seed)
         
     }
 
-    pub fn transfer(ctx: Context<Transfer>,seed: u64)->Result<()>{
-        ctx.accounts.transfer_and_close_vault(seed)   
+    pub fn transfer(ctx: Context<Transfer>,seed: u64, max_receive: u64)->Result<()>{
+        ctx.accounts.transfer_and_close_vault(seed, max_receive)   
+    }
+
+    pub fn take_partial(ctx: Context<TakePartial>, seed: u64, amount_b: u64, min_amount_a: u64) -> Result<()> {
+        ctx.accounts.take_partial(seed, amount_b, min_amount_a)
+    }
+
+    pub fn expire(ctx: Context<Expire>, seed: u64) -> Result<()> {
+        ctx.accounts.expire_and_close_vault(seed)
+    }
+
+    pub fn make_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MakeBasket<'info>>,
+        seed: u64,
+        offered: Vec<BasketLeg>,
+        requested: Vec<BasketLeg>,
+    ) -> Result<()> {
+        ctx.accounts.init_basket_and_deposit(seed, offered, requested, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn take_basket<'info>(ctx: Context<'_, '_, 'info, 'info, TakeBasket<'info>>, seed: u64) -> Result<()> {
+        ctx.accounts.take_basket(seed, ctx.remaining_accounts)
+    }
+
+    pub fn refund_basket<'info>(ctx: Context<'_, '_, 'info, 'info, RefundBasket<'info>>, seed: u64) -> Result<()> {
+        ctx.accounts.refund_basket(seed, ctx.remaining_accounts)
+    }
+
+    pub fn settle_ring<'info>(ctx: Context<'_, '_, 'info, 'info, SettleRing<'info>>) -> Result<()> {
+        ctx.accounts.settle_ring(ctx.remaining_accounts)
+    }
+
+    pub fn counter_offer(ctx: Context<MakeCounterOffer>, seed: u64, amount_b: u64) -> Result<()> {
+        ctx.accounts.counter_offer(seed, amount_b, &ctx.bumps)
+    }
+
+    pub fn accept_counter(ctx: Context<AcceptCounter>, seed: u64) -> Result<()> {
+        ctx.accounts.accept_counter(seed)
+    }
+
+    pub fn withdraw_counter(ctx: Context<WithdrawCounter>) -> Result<()> {
+        ctx.accounts.withdraw_counter()
+    }
+
+    pub fn update_offer(
+        ctx: Context<UpdateOffer>,
+        seed: u64,
+        receive: Option<u64>,
+        top_up: u64,
+        withdraw: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.update_offer(seed, receive, top_up, withdraw, expires_at)
+    }
+
+    pub fn make_arbitrated(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit_amount: u64,
+        counterparty: Pubkey,
+        arbiter: Pubkey,
+    ) -> Result<()> {
+        require!(deposit_amount > 0, EscrowError::InvalidAmount);
+
+        ctx.accounts.init_arbitrated_escrow_and_deposit(seed, deposit_amount, counterparty, arbiter, &ctx.bumps)
+    }
+
+    pub fn release(ctx: Context<Release>, seed: u64) -> Result<()> {
+        ctx.accounts.release_and_close_vault(seed)
+    }
+
+    pub fn raise_dispute(ctx: Context<RaiseDispute>, seed: u64) -> Result<()> {
+        ctx.accounts.raise_dispute(seed)
+    }
+
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, seed: u64, counterparty_bps: u16) -> Result<()> {
+        ctx.accounts.resolve_dispute(seed, counterparty_bps)
+    }
+
+    pub fn make_milestones(
+        ctx: Context<Make>,
+        seed: u64,
+        milestones: Vec<Milestone>,
+        worker: Pubkey,
+        notice_period: i64,
+    ) -> Result<()> {
+        ctx.accounts.init_milestone_escrow_and_deposit(seed, milestones, worker, notice_period, &ctx.bumps)
+    }
+
+    pub fn make_stream(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit_amount: u64,
+        recipient: Pubkey,
+        start: i64,
+        end: i64,
+    ) -> Result<()> {
+        require!(deposit_amount > 0, EscrowError::InvalidAmount);
+
+        ctx.accounts.init_stream_escrow_and_deposit(seed, deposit_amount, recipient, start, end, &ctx.bumps)
+    }
+
+    pub fn withdraw_streamed(ctx: Context<WithdrawStreamed>, seed: u64) -> Result<()> {
+        ctx.accounts.withdraw_streamed(seed)
+    }
+
+    pub fn cancel_stream(ctx: Context<CancelStream>, seed: u64) -> Result<()> {
+        ctx.accounts.cancel_stream(seed)
+    }
+
+    pub fn make_htlc(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit_amount: u64,
+        counterparty: Pubkey,
+        hash: [u8; 32],
+        timeout: i64,
+    ) -> Result<()> {
+        require!(deposit_amount > 0, EscrowError::InvalidAmount);
+
+        ctx.accounts.init_htlc_escrow_and_deposit(seed, deposit_amount, counterparty, hash, timeout, &ctx.bumps)
+    }
+
+    pub fn claim_htlc(ctx: Context<ClaimHtlc>, seed: u64, secret: [u8; 32]) -> Result<()> {
+        ctx.accounts.claim_htlc(seed, secret)
+    }
+
+    pub fn reclaim_htlc(ctx: Context<ReclaimHtlc>, seed: u64) -> Result<()> {
+        ctx.accounts.reclaim_htlc(seed)
+    }
+
+    pub fn approve_milestone(ctx: Context<ApproveMilestone>, seed: u64, index: u8) -> Result<()> {
+        ctx.accounts.approve_milestone(seed, index)
+    }
+
+    pub fn cancel_remaining(ctx: Context<CancelRemaining>, seed: u64) -> Result<()> {
+        ctx.accounts.cancel_remaining(seed)
     }
 }
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{EscrowError, MAX_BASKET_MINTS};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct BasketLeg {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+// like Escrow, but the maker offers several mints and asks for several mints in return, all legs settle in one take_basket
+#[account]
+#[derive(InitSpace)]
+pub struct BasketEscrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    #[max_len(MAX_BASKET_MINTS)]
+    pub offered: Vec<BasketLeg>, // deposited by the maker, each held in a vault owned by the basket
+    #[max_len(MAX_BASKET_MINTS)]
+    pub requested: Vec<BasketLeg>, // paid by the taker straight to the maker's atas
+    pub bump: u8,
+}
+
+impl BasketEscrow {
+    // a side of the basket needs between 1 and MAX_BASKET_MINTS legs, with distinct mints and non zero amounts
+    pub fn validate_legs(legs: &[BasketLeg]) -> Result<()> {
+        require!(!legs.is_empty() && legs.len() <= MAX_BASKET_MINTS, EscrowError::InvalidBasket);
+
+        for (i, leg) in legs.iter().enumerate() {
+            require!(leg.amount > 0, EscrowError::InvalidAmount);
+            require!(legs[..i].iter().all(|other| other.mint != leg.mint), EscrowError::InvalidBasket);
+        }
+
+        Ok(())
+    }
+}
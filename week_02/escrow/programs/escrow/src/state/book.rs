@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::{Escrow, EscrowError, MAX_BOOK_ENTRIES, PRICE_SCALE};
+
+// one page of the open offers of a (mint_a, mint_b) pair, pages are numbered from 0 and opened one after another
+#[account]
+#[derive(InitSpace)]
+pub struct OfferBook {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub page: u32,
+    #[max_len(MAX_BOOK_ENTRIES)]
+    pub entries: Vec<BookEntry>, // unordered, clients sort by price
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct BookEntry {
+    pub escrow: Pubkey,
+    pub price: u64, // mint_b asked per mint_a deposited, scaled by PRICE_SCALE
+}
+
+impl OfferBook {
+    // price of an offer in raw units, rounded down
+    pub fn price_of(deposit: u64, receive: u64) -> u64 {
+        (receive as u128 * PRICE_SCALE as u128 / deposit as u128) as u64
+    }
+
+    pub fn list(&mut self, escrow: Pubkey, price: u64) -> Result<()> {
+        require!(self.entries.len() < MAX_BOOK_ENTRIES, EscrowError::BookPageFull);
+
+        self.entries.push(BookEntry { escrow, price });
+        Ok(())
+    }
+
+    // every instruction that closes an escrow calls this, and a listed one fails unless its page is passed in,
+    // so the book never keeps closed offers. Escrows that were never listed need no page
+    pub fn delist(book: Option<&mut Account<'_, OfferBook>>, escrow: &Account<'_, Escrow>) -> Result<()> {
+        let Some(book) = Self::listed_page(book, escrow)? else {
+            return Ok(());
+        };
+
+        book.entries.retain(|entry| entry.escrow != escrow.key());
+        Ok(())
+    }
+
+    // keeps the listed price in line with an escrow whose deposit or receive changed
+    pub fn reprice(book: Option<&mut Account<'_, OfferBook>>, escrow: &Account<'_, Escrow>) -> Result<()> {
+        let Some(book) = Self::listed_page(book, escrow)? else {
+            return Ok(());
+        };
+
+        let price = Self::price_of(escrow.deposit, escrow.receive);
+        for entry in book.entries.iter_mut().filter(|entry| entry.escrow == escrow.key()) {
+            entry.price = price;
+        }
+        Ok(())
+    }
+
+    // the page a listed escrow is on, None when it is not listed
+    fn listed_page<'a, 'info>(
+        book: Option<&'a mut Account<'info, OfferBook>>,
+        escrow: &Account<'_, Escrow>,
+    ) -> Result<Option<&'a mut Account<'info, OfferBook>>> {
+        let Some(page) = escrow.book_page else {
+            return Ok(None);
+        };
+        let book = book.ok_or(EscrowError::BookAccountMismatch)?;
+        // a page of another pair can have the same number, so the pair has to match as well
+        require!(book.page == page, EscrowError::BookAccountMismatch);
+        require!(book.mint_a == escrow.mint_a && book.mint_b == escrow.mint_b, EscrowError::BookAccountMismatch);
+
+        Ok(Some(book))
+    }
+}
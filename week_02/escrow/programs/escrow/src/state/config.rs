@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+// protocol wide settings of the escrow service, a single pda
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowConfig {
+    pub admin: Pubkey, // can change the fee, the treasury and pause make
+    pub treasury: Pubkey, // receives the fees, in mint_b
+    pub fee_bps: u16, // taken from the mint_b leg of every take, capped at MAX_FEE_BPS
+    pub paused: bool, // when true, no new escrows can be made, existing ones can still be taken and refunded
+    pub bump: u8,
+}
+
+impl EscrowConfig {
+    // fee on amount of mint_b, rounded down so the maker is never charged more than fee_bps
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / 10_000) as u64
+    }
+}
@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+// a taker's proposal to swap an escrow's vault for a different amount of mint_b, the mint_b is escrowed in the counter's own vault
+#[account]
+#[derive(InitSpace)]
+pub struct CounterOffer {
+    pub escrow: Pubkey, // offer this counter answers
+    pub taker: Pubkey,
+    pub amount_a: u64, // vault balance the counter was made for, the counter is void once it changes
+    pub amount_b: u64, // the receive proposed by the taker, already escrowed
+    pub bump: u8,
+}
@@ -1,4 +1,22 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::{EscrowError, MAX_MILESTONES};
+
+pub mod basket;
+pub use basket::*;
+
+pub mod counter;
+pub use counter::*;
+
+pub mod config;
+pub use config::*;
+
+pub mod book;
+pub use book::*;
+
+pub mod oracle;
+pub use oracle::*;
 
 // the escrow pda actually itself becomes the vault, do we don't have to use a vault explicitly here
 #[account]
@@ -10,6 +28,121 @@ pub struct Escrow {
     pub mint_b: Pubkey,
     pub receive: u64,
     pub bump: u8,
+    // fields below were added after the first escrows were made, they only go at the end so older escrows keep their layout
+    // an older escrow is brought up to this size with migrate_escrow
+    pub deposit: u64, // mint_a originally deposited, deposit/receive is the fixed fill ratio
+    pub filled: u64, // mint_b already paid to the maker by partial fills
+    pub remaining: u64, // mint_b still needed to fill the offer completely
+    pub expires_at: Option<i64>, // the offer can not be taken anymore after this timestamp
+    pub expire_bounty: u64, // lamports of the escrow rent paid to whoever cranks expire
+    pub taker: Option<Pubkey>, // when set, only this key can fill the offer, in arbitrated and milestone mode it is the counterparty being paid
+    pub arbiter: Option<Pubkey>, // set for arbitrated escrows, rules on disputes between maker and counterparty
+    pub status: EscrowStatus,
+    #[max_len(MAX_MILESTONES)]
+    pub milestones: Vec<Milestone>, // staged payouts to the counterparty, empty unless made with make_milestones
+    pub notice_period: i64, // seconds between the maker's cancel notice and the refund of unapproved milestones
+    pub cancel_notice_at: Option<i64>, // when the maker gave notice with cancel_remaining
+    pub book_page: Option<u32>, // page of the pair's OfferBook the offer is listed on
+    pub oracle: Option<Pubkey>, // price feed of mint_a in mint_b, when set the taker pays the feed price plus spread_bps instead of receive
+    pub spread_bps: u16,
+    pub max_price_age: Option<i64>, // seconds a feed price can be old when the offer is taken
+    pub collection: Option<Pubkey>, // when set, mint_b is this collection's mint and the offer is filled by any nft verified in it
+    pub stream: Option<Stream>, // vesting schedule of a streaming escrow, the counterparty is the recipient
+    pub hashlock: Option<Hashlock>, // set for htlc escrows, the counterparty claims with the secret before the timeout
+    pub max_conf_bps: Option<u16>, // widest confidence interval of a feed price an oracle offer is taken at, in basis points of the price
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct Milestone {
+    pub amount: u64, // mint_a released to the counterparty when approved
+    pub description_hash: [u8; 32], // hash of the off chain description of the work
+    pub approved: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct Stream {
+    pub start: i64,
+    pub end: i64, // the whole deposit is vested from here on
+    pub withdrawn: u64, // mint_a already claimed by the recipient
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct Hashlock {
+    pub hash: [u8; 32], // sha256 of the secret
+    pub timeout: i64, // the counterparty can claim before it, the maker can reclaim from it on
+}
+
+// an arbitrated escrow goes from Active to Disputed when either party raises a dispute, release and resolve_dispute close it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum EscrowStatus {
+    Active,
+    Disputed,
+}
+
+impl Escrow {
+    // seed, maker, mint_a, mint_b, receive and bump, all an escrow held before partial fills, without the discriminator
+    pub const ORIGINAL_SPACE: usize = 8 + 32 * 3 + 8 + 1;
+
+    // the native mint stands for plain SOL: a native mint_a is held as lamports by the escrow pda, a native mint_b is paid wallet to wallet
+    pub fn is_native_a(&self) -> bool {
+        self.mint_a == native_mint::ID
+    }
+
+    pub fn is_native_b(&self) -> bool {
+        self.mint_b == native_mint::ID
+    }
+
+    // arbitrated escrows only pay out through release and resolve_dispute
+    pub fn is_arbitrated(&self) -> bool {
+        self.arbiter.is_some()
+    }
+
+    // arbitrated, milestone, streaming and htlc escrows pay a counterparty, every other escrow is a swap offer
+    pub fn is_swap(&self) -> bool {
+        !self.is_arbitrated() && self.milestones.is_empty() && self.stream.is_none() && self.hashlock.is_none()
+    }
+
+    pub fn can_be_taken_by(&self, taker: &Pubkey) -> bool {
+        self.taker.is_none_or(|designated| designated == *taker)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    // mint_a owed for filling amount_b more, computed on the cumulative fill so rounding never adds up in the taker's favor
+    pub fn payout_for(&self, amount_b: u64) -> u64 {
+        let paid = self.filled as u128 * self.deposit as u128 / self.receive as u128;
+        let owed = (self.filled + amount_b) as u128 * self.deposit as u128 / self.receive as u128;
+        (owed - paid) as u64
+    }
+
+    // mint_a vested linearly between start and end, rounded down so the recipient is never ahead of the schedule
+    pub fn vested(&self, now: i64) -> u64 {
+        let Some(stream) = self.stream else {
+            return 0;
+        };
+        let elapsed = now.clamp(stream.start, stream.end) - stream.start;
+        (self.deposit as u128 * elapsed as u128 / (stream.end - stream.start) as u128) as u64
+    }
+
+    // mint_b owed for amount_a at the feed price plus the spread, rounded up in the maker's favor
+    pub fn oracle_receive(&self, amount_a: u64, price: u64, expo: i32, decimals_a: u8, decimals_b: u8) -> Result<u64> {
+        let exponent = expo + decimals_b as i32 - decimals_a as i32;
+        let scale = 10u128.checked_pow(exponent.unsigned_abs()).ok_or(EscrowError::InvalidAmount)?;
+
+        let value = (amount_a as u128)
+            .checked_mul(price as u128)
+            .and_then(|value| value.checked_mul(10_000 + self.spread_bps as u128))
+            .ok_or(EscrowError::InvalidAmount)?;
+
+        let receive = match exponent >= 0 {
+            true => value.checked_mul(scale).ok_or(EscrowError::InvalidAmount)?.div_ceil(10_000),
+            false => value.div_ceil(scale.checked_mul(10_000).ok_or(EscrowError::InvalidAmount)?),
+        };
+
+        u64::try_from(receive).map_err(|_| error!(EscrowError::InvalidAmount))
+    }
 }
 
 /*
@@ -18,5 +151,37 @@ pub struct Escrow {
     - this is why we do not need taker account here, because tldr:
         it takes tokens from maker, stores them, and transfers to taker. does not take tokens from taker, hence, do need for it
 
+    - an offer can also be taken in pieces with take_partial, every piece pays out mint_a at the fixed deposit/receive ratio rounded down, the vault and escrow are closed with the last piece
+
+    - an offer with expires_at can not be taken after it expires, anyone can then crank expire to send the vault back to the maker and keep the expire_bounty out of the escrow rent
+
+    - a private offer names its taker at make, nobody else can fill it
+
+    - instead of taking, a taker can escrow mint_b in a CounterOffer proposing a different receive, the maker settles it with accept_counter or the taker backs out with withdraw_counter
+
+    - update_offer changes the terms in place, what is left in the vault becomes the new deposit and the fill accounting starts over from it, takers bound what they pay with max_receive on transfer and what they get with min_amount_a on take_partial
+
+    - an arbitrated escrow (make_arbitrated) is a plain payment to a named counterparty instead of a swap, the maker approves it with release, and if either party raises a dispute the arbiter splits the vault between them with resolve_dispute
+
+    - a milestone escrow (make_milestones) pays the counterparty in tranches, the maker approves each milestone with approve_milestone, and cancel_remaining gives notice and, once notice_period has passed, refunds the unapproved milestones
+
+    - either leg can be native SOL by using the native mint, make, transfer and refund then move lamports instead of tokens and the matching token accounts are left out
+
+    - the EscrowConfig pda charges fee_bps of the mint_b leg to the treasury on every take, and its admin can pause make
+
+    - an offer can be listed on a page of the OfferBook of its pair at make, refund, transfer and expire then take it off again, so takers can find open offers without scanning every escrow
+
+    - an oracle offer (make_oracle) has no fixed receive, transfer prices the deposit with the maker's chosen Pyth feed plus spread_bps at take time and refuses prices older than max_price_age or less certain than max_conf_bps
+
+    - a collection offer (make_collection) asks for any nft of a verified collection instead of one mint_b, transfer checks the metadata of the nft the taker pays with
+
+    - a streaming escrow (make_stream) vests the deposit linearly to the recipient between start and end, the recipient claims it with withdraw_streamed and cancel_stream pays out what is vested and refunds the rest to the maker
+
+    - settle_ring takes a ring of offers where each one asks for the next one's mint_a, and swaps every vault to the maker before it with no outside taker, less the protocol fee
+
+    - an htlc escrow (make_htlc) locks the deposit under sha256(secret) for a counterparty, claim_htlc pays it out for the secret before the timeout and emits the secret for the other chain, reclaim_htlc refunds the maker after it
+
+    - an escrow made by the first version, before partial fills, only holds the fields up to bump, migrate_escrow grows it to the current layout and fills in deposit and remaining from its vault
+
     - we need a seed because we want the maker to be able to have multiple escrow accounts so that he can transfer tokens whenever and however he wants
 */
\ No newline at end of file
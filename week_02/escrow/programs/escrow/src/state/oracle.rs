@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use crate::EscrowError;
+
+// the fields of a Pyth v2 price account that oracle offers read, at their offsets in the account data
+const MAGIC: u32 = 0xa1b2c3d4;
+const VERSION: u32 = 2;
+const PRICE_ACCOUNT: u32 = 3;
+const TRADING: u32 = 1;
+
+const MAGIC_OFFSET: usize = 0;
+const VERSION_OFFSET: usize = 4;
+const TYPE_OFFSET: usize = 8;
+const EXPO_OFFSET: usize = 20;
+const TIMESTAMP_OFFSET: usize = 96;
+const PRICE_OFFSET: usize = 208;
+const CONF_OFFSET: usize = 216;
+const STATUS_OFFSET: usize = 224;
+const FEED_LEN: usize = 240;
+
+// aggregate price of mint_a in mint_b, the price is price * 10^expo
+#[derive(Clone, Copy)]
+pub struct PriceFeed {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+    pub trading: bool,
+}
+
+impl PriceFeed {
+    pub fn load(feed: &AccountInfo) -> Result<PriceFeed> {
+        // local builds with mock-oracle accept mock feeds owned by any program
+        #[cfg(not(feature = "mock-oracle"))]
+        require_keys_eq!(*feed.owner, crate::PYTH_ORACLE_PROGRAM, EscrowError::InvalidPriceFeed);
+
+        let data = feed.try_borrow_data()?;
+        require!(data.len() >= FEED_LEN, EscrowError::InvalidPriceFeed);
+        require!(
+            read_u32(&data, MAGIC_OFFSET) == MAGIC
+                && read_u32(&data, VERSION_OFFSET) == VERSION
+                && read_u32(&data, TYPE_OFFSET) == PRICE_ACCOUNT,
+            EscrowError::InvalidPriceFeed
+        );
+
+        Ok(PriceFeed {
+            price: read_u64(&data, PRICE_OFFSET) as i64,
+            conf: read_u64(&data, CONF_OFFSET),
+            expo: read_u32(&data, EXPO_OFFSET) as i32,
+            publish_time: read_u64(&data, TIMESTAMP_OFFSET) as i64,
+            trading: read_u32(&data, STATUS_OFFSET) == TRADING,
+        })
+    }
+
+    // a price that is not trading, not positive, older than max_age or with a confidence interval wider than max_conf_bps of it
+    // (each when set) can not be used to settle
+    pub fn usable_price(&self, now: i64, max_age: Option<i64>, max_conf_bps: Option<u16>) -> Result<u64> {
+        require!(self.trading && self.price > 0, EscrowError::InvalidPriceFeed);
+        if let Some(max_age) = max_age {
+            require!(now - self.publish_time <= max_age, EscrowError::StalePrice);
+        }
+        if let Some(max_conf_bps) = max_conf_bps {
+            require!(
+                self.conf as u128 * 10_000 <= self.price as u128 * max_conf_bps as u128,
+                EscrowError::PriceTooUncertain
+            );
+        }
+        Ok(self.price as u64)
+    }
+
+    // the same layout as a live feed, so tests can load a mock price account instead of a real one
+    pub fn to_mock_data(&self) -> Vec<u8> {
+        let mut data = vec![0u8; FEED_LEN];
+        data[MAGIC_OFFSET..MAGIC_OFFSET + 4].copy_from_slice(&MAGIC.to_le_bytes());
+        data[VERSION_OFFSET..VERSION_OFFSET + 4].copy_from_slice(&VERSION.to_le_bytes());
+        data[TYPE_OFFSET..TYPE_OFFSET + 4].copy_from_slice(&PRICE_ACCOUNT.to_le_bytes());
+        data[EXPO_OFFSET..EXPO_OFFSET + 4].copy_from_slice(&self.expo.to_le_bytes());
+        data[TIMESTAMP_OFFSET..TIMESTAMP_OFFSET + 8].copy_from_slice(&self.publish_time.to_le_bytes());
+        data[PRICE_OFFSET..PRICE_OFFSET + 8].copy_from_slice(&self.price.to_le_bytes());
+        data[CONF_OFFSET..CONF_OFFSET + 8].copy_from_slice(&self.conf.to_le_bytes());
+        let status: u32 = if self.trading { TRADING } else { 0 };
+        data[STATUS_OFFSET..STATUS_OFFSET + 4].copy_from_slice(&status.to_le_bytes());
+        data
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
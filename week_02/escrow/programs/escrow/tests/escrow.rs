@@ -0,0 +1,1942 @@
+use anchor_lang::{
+    error::{ErrorCode, ERROR_CODE_OFFSET},
+    prelude::{AccountInfo, Clock, ProgramData, Pubkey},
+    solana_program::{entrypoint::ProgramResult, program_pack::Pack},
+    system_program, AccountDeserialize, AccountSerialize, Discriminator, InstructionData, Owner, ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, spl_associated_token_account},
+    metadata::mpl_token_metadata::{
+        accounts::{MasterEdition, Metadata},
+        types::{Collection, Key},
+    },
+    token::spl_token,
+    token_2022::spl_token_2022::{self, extension::StateWithExtensions},
+};
+use borsh::BorshSerialize;
+use escrow::{error::EscrowError, BasketLeg, Escrow, Milestone, PriceFeed};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    compute_budget::ComputeBudgetInstruction,
+    hash::hash,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use solana_system_interface::instruction as system_instruction;
+
+const SEED: u64 = 7;
+const DEPOSIT: u64 = 1_000_000;
+const RECEIVE: u64 = 2_000_000;
+const FUNDS: u64 = 10_000_000;
+
+// anchor's entrypoint wants the account slice to live as long as the accounts in it
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    escrow::entry(program_id, accounts, data)
+}
+
+struct Offer {
+    ctx: ProgramTestContext,
+    token_program: Pubkey,
+    maker: Keypair,
+    taker: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    config: Pubkey,
+    maker_ata_a: Pubkey,
+    maker_ata_b: Pubkey,
+    taker_ata_a: Pubkey,
+    taker_ata_b: Pubkey,
+    escrow: Pubkey,
+    vault: Pubkey,
+    book: Option<Pubkey>, // page 0 of the pair's book, passed to everything that lists or delists once opened
+    price_feed: Option<Pubkey>, // passed to transfer, for oracle offers
+    nft: Option<Pubkey>, // paid in transfer instead of mint_b, for collection offers
+    nonce: u32,
+}
+
+impl Offer {
+    // two fresh mints of token_program, the maker holds mint_a and the taker holds taker_funds of mint_b
+    async fn new(token_program: Pubkey, taker_funds: u64) -> Offer {
+        let mut program_test = ProgramTest::new("escrow", escrow::ID, processor!(process_instruction));
+        program_test.prefer_bpf(false);
+        let ctx = program_test.start_with_context().await;
+
+        let maker = Keypair::new();
+        let escrow = Pubkey::find_program_address(&[b"escrow", maker.pubkey().as_ref(), SEED.to_le_bytes().as_ref()], &escrow::ID).0;
+
+        let mut offer = Offer {
+            ctx,
+            token_program,
+            maker,
+            taker: Keypair::new(),
+            mint_a: Pubkey::default(),
+            mint_b: Pubkey::default(),
+            config: Pubkey::find_program_address(&[b"config"], &escrow::ID).0,
+            maker_ata_a: Pubkey::default(),
+            maker_ata_b: Pubkey::default(),
+            taker_ata_a: Pubkey::default(),
+            taker_ata_b: Pubkey::default(),
+            escrow,
+            vault: Pubkey::default(),
+            book: None,
+            price_feed: None,
+            nft: None,
+            nonce: 0,
+        };
+
+        offer.airdrop(&offer.maker.pubkey(), 1_000_000_000).await;
+        offer.airdrop(&offer.taker.pubkey(), 1_000_000_000).await;
+
+        offer.mint_a = offer.create_mint().await;
+        offer.mint_b = offer.create_mint().await;
+        offer.vault = offer.ata(&escrow, &offer.mint_a);
+
+        let (maker, taker) = (offer.maker.pubkey(), offer.taker.pubkey());
+        offer.maker_ata_a = offer.create_ata(&maker, offer.mint_a, FUNDS).await;
+        offer.maker_ata_b = offer.create_ata(&maker, offer.mint_b, 0).await;
+        offer.taker_ata_a = offer.create_ata(&taker, offer.mint_a, 0).await;
+        offer.taker_ata_b = offer.create_ata(&taker, offer.mint_b, taker_funds).await;
+
+        let admin = offer.ctx.payer.insecure_clone();
+        offer.set_upgrade_authority(&admin.pubkey()).await;
+        offer.initialize_config(&admin).await.unwrap();
+        offer
+    }
+
+    fn ata(&self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, mint, &self.token_program)
+    }
+
+    async fn airdrop(&mut self, to: &Pubkey, lamports: u64) {
+        let ix = system_instruction::transfer(&self.ctx.payer.pubkey(), to, lamports);
+        self.send(ix, &[]).await.unwrap();
+    }
+
+    async fn create_mint(&mut self) -> Pubkey {
+        let payer = self.ctx.payer.insecure_clone();
+        let mint = Keypair::new();
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &payer.pubkey(),
+                    &mint.pubkey(),
+                    rent.minimum_balance(spl_token::state::Mint::LEN),
+                    spl_token::state::Mint::LEN as u64,
+                    &self.token_program,
+                ),
+                spl_token_2022::instruction::initialize_mint2(&self.token_program, &mint.pubkey(), &payer.pubkey(), None, 6).unwrap(),
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &mint],
+            self.ctx.last_blockhash,
+        );
+        self.ctx.banks_client.process_transaction(tx).await.unwrap();
+
+        mint.pubkey()
+    }
+
+    async fn create_ata(&mut self, owner: &Pubkey, mint: Pubkey, amount: u64) -> Pubkey {
+        let payer = self.ctx.payer.pubkey();
+        let ata = self.ata(owner, &mint);
+
+        let create = spl_associated_token_account::instruction::create_associated_token_account(&payer, owner, &mint, &self.token_program);
+        self.send(create, &[]).await.unwrap();
+
+        if amount > 0 {
+            let mint_to = spl_token_2022::instruction::mint_to(&self.token_program, &mint, &ata, &payer, &[], amount).unwrap();
+            self.send(mint_to, &[]).await.unwrap();
+        }
+
+        ata
+    }
+
+    // the test payer pays the fees, so the lamports of maker and taker only move through the program
+    // the nonce makes otherwise identical transactions unique, so they are not rejected as already processed
+    async fn send(&mut self, ix: Instruction, signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        self.nonce += 1;
+        let unique = ComputeBudgetInstruction::set_compute_unit_limit(1_000_000 + self.nonce);
+
+        let payer = self.ctx.payer.insecure_clone();
+        let signers: Vec<&Keypair> = [&payer].into_iter().chain(signers.iter().copied()).collect();
+        let tx = Transaction::new_signed_with_payer(&[unique, ix], Some(&payer.pubkey()), &signers, self.ctx.last_blockhash);
+        self.ctx.banks_client.process_transaction(tx).await
+    }
+
+    fn program_data(&self) -> Pubkey {
+        Pubkey::find_program_address(&[escrow::ID.as_ref()], &ProgramData::owner()).0
+    }
+
+    // the native processor is not deployed through the upgradeable loader, so its program data account is written by hand
+    async fn set_upgrade_authority(&mut self, authority: &Pubkey) {
+        // bincode layout of UpgradeableLoaderState::ProgramData: variant, slot, Option<Pubkey>
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(authority.as_ref());
+
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let program_data = Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: ProgramData::owner(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.ctx.set_account(&self.program_data(), &program_data.into());
+    }
+
+    // the treasury is the admin
+    async fn initialize_config(&mut self, admin: &Keypair) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::InitializeConfig {
+                admin: admin.pubkey(),
+                config: self.config,
+                program_data: self.program_data(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::InitializeConfig { fee_bps: 0, treasury: admin.pubkey() }.data(),
+        };
+        self.send(ix, &[admin]).await
+    }
+
+    async fn set_fee(&mut self, fee_bps: u16) -> Result<(), BanksClientError> {
+        let admin = self.ctx.payer.pubkey();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::UpdateConfig { admin, config: self.config }.to_account_metas(None),
+            data: escrow::instruction::SetFee { fee_bps }.data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    async fn make(&mut self, deposit_amount: u64, receive: u64) -> Result<(), BanksClientError> {
+        self.make_expiring(deposit_amount, receive, None, 0).await
+    }
+
+    async fn make_expiring(&mut self, deposit_amount: u64, receive: u64, expires_at: Option<i64>, expire_bounty: u64) -> Result<(), BanksClientError> {
+        self.make_for(deposit_amount, receive, expires_at, expire_bounty, None).await
+    }
+
+    // with a native mint_a the deposit is held by the escrow itself, so no token accounts are passed
+    // a private offer can only be filled by taker
+    async fn make_for(
+        &mut self,
+        deposit_amount: u64,
+        receive: u64,
+        expires_at: Option<i64>,
+        expire_bounty: u64,
+        taker: Option<Pubkey>,
+    ) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let native = self.mint_a == spl_token::native_mint::ID;
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Make {
+                maker: maker.pubkey(),
+                config: self.config,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                maker_ata_a: (!native).then_some(self.maker_ata_a),
+                escrow: self.escrow,
+                escrow_vault: (!native).then_some(self.vault),
+                book: self.book,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::Make {
+                seed: SEED,
+                deposit_amount,
+                receive,
+                expires_at,
+                expire_bounty,
+                taker,
+            }
+            .data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    async fn expire(&mut self, caller: &Keypair) -> Result<(), BanksClientError> {
+        let native = self.mint_a == spl_token::native_mint::ID;
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Expire {
+                caller: caller.pubkey(),
+                maker: self.maker.pubkey(),
+                mint_a: self.mint_a,
+                maker_ata_a: (!native).then_some(self.maker_ata_a),
+                escrow: self.escrow,
+                vault: (!native).then_some(self.vault),
+                book: self.book,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::Expire { seed: SEED }.data(),
+        };
+        self.send(ix, &[caller]).await
+    }
+
+    // prices the deposit at the feed plus spread_bps, the feed is not checked until the offer is taken
+    async fn make_oracle(
+        &mut self,
+        deposit_amount: u64,
+        oracle: Pubkey,
+        spread_bps: u16,
+        max_price_age: Option<i64>,
+        max_conf_bps: Option<u16>,
+    ) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Make {
+                maker: maker.pubkey(),
+                config: self.config,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                maker_ata_a: Some(self.maker_ata_a),
+                escrow: self.escrow,
+                escrow_vault: Some(self.vault),
+                book: None,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::MakeOracle {
+                seed: SEED,
+                deposit_amount,
+                oracle,
+                spread_bps,
+                max_price_age,
+                max_conf_bps,
+                expires_at: None,
+                taker: None,
+            }
+            .data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    // the mock-oracle build accepts a feed owned by any program, so the mock data is all a feed needs
+    fn set_price_feed(&mut self, address: Pubkey, feed: PriceFeed) {
+        let data = feed.to_mock_data();
+        let price_feed = Account { lamports: 1_000_000_000, data, owner: Pubkey::new_unique(), executable: false, rent_epoch: 0 };
+        self.ctx.set_account(&address, &price_feed.into());
+    }
+
+    async fn make_collection(&mut self, deposit_amount: u64) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Make {
+                maker: maker.pubkey(),
+                config: self.config,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                maker_ata_a: Some(self.maker_ata_a),
+                escrow: self.escrow,
+                escrow_vault: Some(self.vault),
+                book: None,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::MakeCollection { seed: SEED, deposit_amount, expires_at: None, taker: None }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    // an nft held by the taker, with the metadata and master edition token metadata would have written for it
+    async fn create_nft(&mut self, collection: Pubkey, verified: bool) -> Pubkey {
+        let nft = self.create_mint().await;
+        let taker = self.taker.pubkey();
+        self.create_ata(&taker, nft, 1).await;
+
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: self.ctx.payer.pubkey(),
+            mint: nft,
+            name: String::from("nft"),
+            symbol: String::new(),
+            uri: String::new(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: None,
+            collection: Some(Collection { verified, key: collection }),
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        };
+        let master_edition = MasterEdition { key: Key::MasterEditionV2, supply: 0, max_supply: Some(0) };
+
+        self.set_metadata_account(Metadata::find_pda(&nft).0, metadata.try_to_vec().unwrap()).await;
+        self.set_metadata_account(MasterEdition::find_pda(&nft).0, master_edition.try_to_vec().unwrap()).await;
+        nft
+    }
+
+    async fn set_metadata_account(&mut self, address: Pubkey, data: Vec<u8>) {
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let account = Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: anchor_spl::metadata::mpl_token_metadata::ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.ctx.set_account(&address, &account.into());
+    }
+
+    async fn refund(&mut self, maker: &Keypair) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Refund {
+                maker: maker.pubkey(),
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                maker_ata_a: Some(self.ata(&maker.pubkey(), &self.mint_a)),
+                escrow: self.escrow,
+                vault: Some(self.vault),
+                book: self.book,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::Refund { seed: SEED }.data(),
+        };
+        self.send(ix, &[maker]).await
+    }
+
+    async fn migrate_escrow(&mut self) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::MigrateEscrow {
+                payer: self.ctx.payer.pubkey(),
+                maker: self.maker.pubkey(),
+                mint_a: self.mint_a,
+                escrow: self.escrow,
+                vault: Some(self.vault),
+                system_program: system_program::ID,
+                token_program: self.token_program,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::MigrateEscrow { seed: SEED }.data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    // a collection offer is paid with self.nft instead of mint_b, together with the nft's metadata and master edition
+    async fn transfer(&mut self) -> Result<(), BanksClientError> {
+        self.transfer_within(u64::MAX).await
+    }
+
+    async fn transfer_within(&mut self, max_receive: u64) -> Result<(), BanksClientError> {
+        let taker = self.taker.insecure_clone();
+        let mint_b = self.nft.unwrap_or(self.mint_b);
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Transfer {
+                maker: self.maker.pubkey(),
+                taker: taker.pubkey(),
+                mint_a: self.mint_a,
+                mint_b,
+                config: self.config,
+                treasury: self.ctx.payer.pubkey(),
+                treasury_ata_b: None,
+                maker_ata_b: Some(self.ata(&self.maker.pubkey(), &mint_b)),
+                taker_ata_a: Some(self.taker_ata_a),
+                taker_ata_b: Some(self.ata(&taker.pubkey(), &mint_b)),
+                escrow: self.escrow,
+                vault: Some(self.vault),
+                book: self.book,
+                price_feed: self.price_feed,
+                metadata: self.nft.map(|nft| Metadata::find_pda(&nft).0),
+                master_edition: self.nft.map(|nft| MasterEdition::find_pda(&nft).0),
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::Transfer { seed: SEED, max_receive }.data(),
+        };
+        self.send(ix, &[&taker]).await
+    }
+
+    async fn take_partial(&mut self, amount_b: u64) -> Result<(), BanksClientError> {
+        self.take_partial_within(amount_b, 0).await
+    }
+
+    async fn take_partial_within(&mut self, amount_b: u64, min_amount_a: u64) -> Result<(), BanksClientError> {
+        let taker = self.taker.insecure_clone();
+        let treasury = self.ctx.payer.pubkey();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::TakePartial {
+                maker: self.maker.pubkey(),
+                taker: taker.pubkey(),
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                config: self.config,
+                treasury,
+                treasury_ata_b: self.ata(&treasury, &self.mint_b),
+                maker_ata_b: self.maker_ata_b,
+                taker_ata_a: self.taker_ata_a,
+                taker_ata_b: self.taker_ata_b,
+                escrow: self.escrow,
+                vault: self.vault,
+                book: self.book,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::TakePartial { seed: SEED, amount_b, min_amount_a }.data(),
+        };
+        self.send(ix, &[&taker]).await
+    }
+
+    fn basket(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"basket", self.maker.pubkey().as_ref(), SEED.to_le_bytes().as_ref()], &escrow::ID).0
+    }
+
+    // the maker offers every leg of offered for every leg of requested
+    async fn make_basket(&mut self, offered: &[BasketLeg], requested: &[BasketLeg]) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let basket = self.basket();
+        let mut accounts = escrow::accounts::MakeBasket {
+            maker: maker.pubkey(),
+            config: self.config,
+            basket,
+            system_program: system_program::ID,
+            token_program: self.token_program,
+            associated_token_program: spl_associated_token_account::ID,
+        }
+        .to_account_metas(None);
+
+        // [mint, maker_ata, vault] for every offered leg
+        for leg in offered {
+            accounts.extend([
+                AccountMeta::new_readonly(leg.mint, false),
+                AccountMeta::new(self.ata(&maker.pubkey(), &leg.mint), false),
+                AccountMeta::new(self.ata(&basket, &leg.mint), false),
+            ]);
+        }
+
+        let data = escrow::instruction::MakeBasket { seed: SEED, offered: offered.to_vec(), requested: requested.to_vec() }.data();
+        let ix = Instruction { program_id: escrow::ID, accounts, data };
+        self.send(ix, &[&maker]).await
+    }
+
+    // a second mint on each side, the maker offers mint_a and mint_c for mint_b and mint_d, the taker holds taker_funds_d of mint_d
+    async fn two_by_two_basket(&mut self, taker_funds_d: u64) -> (Vec<BasketLeg>, Vec<BasketLeg>) {
+        let (maker, taker) = (self.maker.pubkey(), self.taker.pubkey());
+        let mint_c = self.create_mint().await;
+        let mint_d = self.create_mint().await;
+        self.create_ata(&maker, mint_c, FUNDS).await;
+        self.create_ata(&taker, mint_d, taker_funds_d).await;
+
+        let offered = vec![BasketLeg { mint: self.mint_a, amount: DEPOSIT }, BasketLeg { mint: mint_c, amount: 2 * DEPOSIT }];
+        let requested = vec![BasketLeg { mint: self.mint_b, amount: RECEIVE }, BasketLeg { mint: mint_d, amount: 2 * RECEIVE }];
+        (offered, requested)
+    }
+
+    async fn refund_basket(&mut self, offered: &[BasketLeg]) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let basket = self.basket();
+        let mut accounts = escrow::accounts::RefundBasket {
+            maker: maker.pubkey(),
+            basket,
+            system_program: system_program::ID,
+            token_program: self.token_program,
+            associated_token_program: spl_associated_token_account::ID,
+        }
+        .to_account_metas(None);
+
+        // [mint, maker_ata, vault] for every offered leg
+        for leg in offered {
+            accounts.extend([
+                AccountMeta::new_readonly(leg.mint, false),
+                AccountMeta::new(self.ata(&maker.pubkey(), &leg.mint), false),
+                AccountMeta::new(self.ata(&basket, &leg.mint), false),
+            ]);
+        }
+
+        let ix = Instruction { program_id: escrow::ID, accounts, data: escrow::instruction::RefundBasket { seed: SEED }.data() };
+        self.send(ix, &[&maker]).await
+    }
+
+    // the remaining accounts take_basket expects for these legs, in order
+    fn take_basket_accounts(&self, offered: &[BasketLeg], requested: &[BasketLeg]) -> Vec<AccountMeta> {
+        let (maker, taker, treasury, basket) = (self.maker.pubkey(), self.taker.pubkey(), self.ctx.payer.pubkey(), self.basket());
+        let mut accounts = Vec::new();
+
+        // [mint, taker_ata, maker_ata, treasury_ata] for every requested leg, then [mint, vault, taker_ata] for every offered leg
+        for leg in requested {
+            accounts.extend([
+                AccountMeta::new_readonly(leg.mint, false),
+                AccountMeta::new(self.ata(&taker, &leg.mint), false),
+                AccountMeta::new(self.ata(&maker, &leg.mint), false),
+                AccountMeta::new(self.ata(&treasury, &leg.mint), false),
+            ]);
+        }
+        for leg in offered {
+            accounts.extend([
+                AccountMeta::new_readonly(leg.mint, false),
+                AccountMeta::new(self.ata(&basket, &leg.mint), false),
+                AccountMeta::new(self.ata(&taker, &leg.mint), false),
+            ]);
+        }
+
+        accounts
+    }
+
+    async fn take_basket(&mut self, remaining_accounts: Vec<AccountMeta>) -> Result<(), BanksClientError> {
+        let taker = self.taker.insecure_clone();
+        let mut accounts = escrow::accounts::TakeBasket {
+            maker: self.maker.pubkey(),
+            taker: taker.pubkey(),
+            config: self.config,
+            treasury: self.ctx.payer.pubkey(),
+            basket: self.basket(),
+            system_program: system_program::ID,
+            token_program: self.token_program,
+            associated_token_program: spl_associated_token_account::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(remaining_accounts);
+
+        let ix = Instruction { program_id: escrow::ID, accounts, data: escrow::instruction::TakeBasket { seed: SEED }.data() };
+        self.send(ix, &[&taker]).await
+    }
+
+    fn counter(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"counter", self.escrow.as_ref(), self.taker.pubkey().as_ref()], &escrow::ID).0
+    }
+
+    // the taker proposes amount_b for the whole vault
+    async fn counter_offer(&mut self, amount_b: u64) -> Result<(), BanksClientError> {
+        let taker = self.taker.insecure_clone();
+        let counter = self.counter();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::MakeCounterOffer {
+                taker: taker.pubkey(),
+                maker: self.maker.pubkey(),
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                taker_ata_b: self.taker_ata_b,
+                escrow: self.escrow,
+                vault: self.vault,
+                counter,
+                counter_vault: self.ata(&counter, &self.mint_b),
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::CounterOffer { seed: SEED, amount_b }.data(),
+        };
+        self.send(ix, &[&taker]).await
+    }
+
+    async fn accept_counter(&mut self) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let (treasury, counter) = (self.ctx.payer.pubkey(), self.counter());
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::AcceptCounter {
+                maker: maker.pubkey(),
+                taker: self.taker.pubkey(),
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                config: self.config,
+                treasury,
+                treasury_ata_b: self.ata(&treasury, &self.mint_b),
+                maker_ata_b: self.maker_ata_b,
+                taker_ata_a: self.taker_ata_a,
+                escrow: self.escrow,
+                vault: self.vault,
+                counter,
+                counter_vault: self.ata(&counter, &self.mint_b),
+                book: self.book,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::AcceptCounter { seed: SEED }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    async fn withdraw_counter(&mut self) -> Result<(), BanksClientError> {
+        let taker = self.taker.insecure_clone();
+        let counter = self.counter();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::WithdrawCounter {
+                taker: taker.pubkey(),
+                mint_b: self.mint_b,
+                taker_ata_b: self.taker_ata_b,
+                counter,
+                counter_vault: self.ata(&counter, &self.mint_b),
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::WithdrawCounter {}.data(),
+        };
+        self.send(ix, &[&taker]).await
+    }
+
+    // the deposit is owed to the taker as counterparty, arbiter rules on disputes
+    async fn make_arbitrated(&mut self, deposit_amount: u64, arbiter: Pubkey) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Make {
+                maker: maker.pubkey(),
+                config: self.config,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                maker_ata_a: Some(self.maker_ata_a),
+                escrow: self.escrow,
+                escrow_vault: Some(self.vault),
+                book: None,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::MakeArbitrated { seed: SEED, deposit_amount, counterparty: self.taker.pubkey(), arbiter }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    async fn release(&mut self) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Release {
+                maker: maker.pubkey(),
+                counterparty: self.taker.pubkey(),
+                mint_a: self.mint_a,
+                counterparty_ata_a: self.taker_ata_a,
+                escrow: self.escrow,
+                vault: self.vault,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::Release { seed: SEED }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    async fn raise_dispute(&mut self, party: &Keypair) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::RaiseDispute { party: party.pubkey(), maker: self.maker.pubkey(), escrow: self.escrow }.to_account_metas(None),
+            data: escrow::instruction::RaiseDispute { seed: SEED }.data(),
+        };
+        self.send(ix, &[party]).await
+    }
+
+    async fn resolve_dispute(&mut self, arbiter: &Keypair, counterparty_bps: u16) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::ResolveDispute {
+                arbiter: arbiter.pubkey(),
+                maker: self.maker.pubkey(),
+                counterparty: self.taker.pubkey(),
+                mint_a: self.mint_a,
+                maker_ata_a: self.maker_ata_a,
+                counterparty_ata_a: self.taker_ata_a,
+                escrow: self.escrow,
+                vault: self.vault,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::ResolveDispute { seed: SEED, counterparty_bps }.data(),
+        };
+        self.send(ix, &[arbiter]).await
+    }
+
+    // the taker is the worker paid milestone by milestone
+    async fn make_milestones(&mut self, amounts: &[u64], notice_period: i64) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let milestones = amounts
+            .iter()
+            .map(|&amount| Milestone { amount, description_hash: hash(&amount.to_le_bytes()).to_bytes(), approved: false })
+            .collect();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Make {
+                maker: maker.pubkey(),
+                config: self.config,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                maker_ata_a: Some(self.maker_ata_a),
+                escrow: self.escrow,
+                escrow_vault: Some(self.vault),
+                book: None,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::MakeMilestones { seed: SEED, milestones, worker: self.taker.pubkey(), notice_period }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    async fn approve_milestone(&mut self, index: u8) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::ApproveMilestone {
+                maker: maker.pubkey(),
+                worker: self.taker.pubkey(),
+                mint_a: self.mint_a,
+                worker_ata_a: self.taker_ata_a,
+                escrow: self.escrow,
+                vault: self.vault,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::ApproveMilestone { seed: SEED, index }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    async fn cancel_remaining(&mut self) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::CancelRemaining {
+                maker: maker.pubkey(),
+                mint_a: self.mint_a,
+                maker_ata_a: self.maker_ata_a,
+                escrow: self.escrow,
+                vault: self.vault,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::CancelRemaining { seed: SEED }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    // the taker offers their mint_b for the maker's mint_a, the two offers then close a ring of two
+    async fn make_reverse(&mut self, deposit_amount: u64, receive: u64) -> Result<Pubkey, BanksClientError> {
+        let taker = self.taker.insecure_clone();
+        let escrow = Pubkey::find_program_address(&[b"escrow", taker.pubkey().as_ref(), SEED.to_le_bytes().as_ref()], &escrow::ID).0;
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Make {
+                maker: taker.pubkey(),
+                config: self.config,
+                mint_a: self.mint_b,
+                mint_b: self.mint_a,
+                maker_ata_a: Some(self.taker_ata_b),
+                escrow,
+                escrow_vault: Some(self.ata(&escrow, &self.mint_b)),
+                book: None,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::Make {
+                seed: SEED,
+                deposit_amount,
+                receive,
+                expires_at: None,
+                expire_bounty: 0,
+                taker: None,
+            }
+            .data(),
+        };
+        self.send(ix, &[&taker]).await?;
+        Ok(escrow)
+    }
+
+    async fn settle_ring(&mut self, escrows: &[(Pubkey, Pubkey, Pubkey, Pubkey)]) -> Result<(), BanksClientError> {
+        let treasury = self.ctx.payer.pubkey();
+        let mut accounts = escrow::accounts::SettleRing {
+            settler: treasury,
+            config: self.config,
+            treasury,
+            system_program: system_program::ID,
+            token_program: self.token_program,
+            associated_token_program: spl_associated_token_account::ID,
+        }
+        .to_account_metas(None);
+
+        // [escrow, maker, mint_a, vault, maker_ata_b, treasury_ata_a] for every escrow of the ring
+        for (escrow, maker, mint_a, mint_b) in escrows {
+            accounts.extend([
+                AccountMeta::new(*escrow, false),
+                AccountMeta::new(*maker, false),
+                AccountMeta::new_readonly(*mint_a, false),
+                AccountMeta::new(self.ata(escrow, mint_a), false),
+                AccountMeta::new(self.ata(maker, mint_b), false),
+                AccountMeta::new(self.ata(&treasury, mint_a), false),
+            ]);
+        }
+
+        let ix = Instruction { program_id: escrow::ID, accounts, data: escrow::instruction::SettleRing {}.data() };
+        self.send(ix, &[]).await
+    }
+
+    // locks the deposit for the taker under hash until timeout
+    async fn make_htlc(&mut self, deposit_amount: u64, hash: [u8; 32], timeout: i64) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Make {
+                maker: maker.pubkey(),
+                config: self.config,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                maker_ata_a: Some(self.maker_ata_a),
+                escrow: self.escrow,
+                escrow_vault: Some(self.vault),
+                book: None,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::MakeHtlc { seed: SEED, deposit_amount, counterparty: self.taker.pubkey(), hash, timeout }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    async fn claim_htlc(&mut self, secret: [u8; 32]) -> Result<(), BanksClientError> {
+        let taker = self.taker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::ClaimHtlc {
+                counterparty: taker.pubkey(),
+                maker: self.maker.pubkey(),
+                mint_a: self.mint_a,
+                counterparty_ata_a: self.taker_ata_a,
+                escrow: self.escrow,
+                vault: self.vault,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::ClaimHtlc { seed: SEED, secret }.data(),
+        };
+        self.send(ix, &[&taker]).await
+    }
+
+    async fn reclaim_htlc(&mut self) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::ReclaimHtlc {
+                maker: maker.pubkey(),
+                mint_a: self.mint_a,
+                maker_ata_a: self.maker_ata_a,
+                escrow: self.escrow,
+                vault: self.vault,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::ReclaimHtlc { seed: SEED }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    // streams the deposit to the taker from start to end
+    async fn make_stream(&mut self, deposit_amount: u64, start: i64, end: i64) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Make {
+                maker: maker.pubkey(),
+                config: self.config,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                maker_ata_a: Some(self.maker_ata_a),
+                escrow: self.escrow,
+                escrow_vault: Some(self.vault),
+                book: None,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::MakeStream { seed: SEED, deposit_amount, recipient: self.taker.pubkey(), start, end }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    async fn withdraw_streamed(&mut self) -> Result<(), BanksClientError> {
+        let taker = self.taker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::WithdrawStreamed {
+                recipient: taker.pubkey(),
+                maker: self.maker.pubkey(),
+                mint_a: self.mint_a,
+                recipient_ata_a: self.taker_ata_a,
+                escrow: self.escrow,
+                vault: self.vault,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::WithdrawStreamed { seed: SEED }.data(),
+        };
+        self.send(ix, &[&taker]).await
+    }
+
+    async fn cancel_stream(&mut self) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::CancelStream {
+                maker: maker.pubkey(),
+                recipient: self.taker.pubkey(),
+                mint_a: self.mint_a,
+                maker_ata_a: self.maker_ata_a,
+                recipient_ata_a: self.taker_ata_a,
+                escrow: self.escrow,
+                vault: self.vault,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::CancelStream { seed: SEED }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    async fn open_book_page(&mut self) -> Result<(), BanksClientError> {
+        let book = escrow::client::book_address(&self.mint_a, &self.mint_b, 0);
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::OpenBookPage {
+                payer: self.ctx.payer.pubkey(),
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                previous: None,
+                book,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::OpenBookPage { page: 0 }.data(),
+        };
+        self.send(ix, &[]).await?;
+
+        self.book = Some(book);
+        Ok(())
+    }
+
+    async fn update_offer(&mut self, receive: Option<u64>, top_up: u64, withdraw: u64) -> Result<(), BanksClientError> {
+        let maker = self.maker.insecure_clone();
+        let ix = Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::UpdateOffer {
+                maker: maker.pubkey(),
+                mint_a: self.mint_a,
+                maker_ata_a: self.maker_ata_a,
+                escrow: self.escrow,
+                vault: self.vault,
+                book: self.book,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+                associated_token_program: spl_associated_token_account::ID,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::UpdateOffer { seed: SEED, receive, top_up, withdraw, expires_at: None }.data(),
+        };
+        self.send(ix, &[&maker]).await
+    }
+
+    // the open offers of the pair as the client helper reads them, the book pages are fetched up front since the helper is not async
+    async fn open_offers(&mut self) -> Vec<escrow::BookEntry> {
+        let mut pages = std::collections::HashMap::new();
+        for page in 0..2 {
+            let address = escrow::client::book_address(&self.mint_a, &self.mint_b, page);
+            if let Some(account) = self.ctx.banks_client.get_account(address).await.unwrap() {
+                pages.insert(address, account.data);
+            }
+        }
+
+        escrow::client::open_offers(&self.mint_a, &self.mint_b, |address| pages.get(address).cloned()).unwrap()
+    }
+
+    async fn set_time(&mut self, unix_timestamp: i64) {
+        let mut clock: Clock = self.ctx.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp = unix_timestamp;
+        self.ctx.set_sysvar(&clock);
+    }
+
+    async fn balance(&mut self, token_account: Pubkey) -> u64 {
+        let account = self.ctx.banks_client.get_account(token_account).await.unwrap().unwrap();
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data).unwrap().base.amount
+    }
+
+    async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.ctx.banks_client.get_balance(address).await.unwrap()
+    }
+
+    async fn exists(&mut self, address: Pubkey) -> bool {
+        self.ctx.banks_client.get_account(address).await.unwrap().is_some()
+    }
+}
+
+fn assert_error(result: Result<(), BanksClientError>, code: u32) {
+    match result.expect_err("instruction should have failed").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(actual)) => assert_eq!(actual, code),
+        other => panic!("expected custom error {code}, got {other:?}"),
+    }
+}
+
+fn assert_escrow_error(result: Result<(), BanksClientError>, error: EscrowError) {
+    assert_error(result, ERROR_CODE_OFFSET + error as u32);
+}
+
+#[tokio::test]
+async fn make_deposits_into_the_vault() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+
+    assert_eq!(offer.balance(offer.vault).await, DEPOSIT);
+    assert_eq!(offer.balance(offer.maker_ata_a).await, FUNDS - DEPOSIT);
+}
+
+#[tokio::test]
+async fn only_the_upgrade_authority_initializes_the_config() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+
+    // back to a fresh deployment, before the config exists
+    offer.ctx.set_account(&offer.config, &Account::default().into());
+
+    let stranger = Keypair::new();
+    offer.airdrop(&stranger.pubkey(), 1_000_000_000).await;
+    assert_escrow_error(offer.initialize_config(&stranger).await, EscrowError::UnauthorizedAdmin);
+
+    let admin = offer.ctx.payer.insecure_clone();
+    offer.initialize_config(&admin).await.unwrap();
+    assert!(offer.exists(offer.config).await);
+}
+
+#[tokio::test]
+async fn refund_returns_the_deposit_and_the_rent() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let lamports = offer.lamports(offer.maker.pubkey()).await;
+
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+    assert!(offer.lamports(offer.maker.pubkey()).await < lamports);
+
+    let maker = offer.maker.insecure_clone();
+    offer.refund(&maker).await.unwrap();
+
+    assert_eq!(offer.balance(offer.maker_ata_a).await, FUNDS);
+    assert_eq!(offer.lamports(offer.maker.pubkey()).await, lamports);
+    assert!(!offer.exists(offer.escrow).await);
+    assert!(!offer.exists(offer.vault).await);
+}
+
+#[tokio::test]
+async fn transfer_swaps_and_refunds_the_rent_to_the_maker() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+
+    let rent = offer.lamports(offer.escrow).await + offer.lamports(offer.vault).await;
+    let lamports = offer.lamports(offer.maker.pubkey()).await;
+
+    offer.transfer().await.unwrap();
+
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert_eq!(offer.balance(offer.maker_ata_b).await, RECEIVE);
+    assert_eq!(offer.balance(offer.taker_ata_b).await, FUNDS - RECEIVE);
+    assert_eq!(offer.lamports(offer.maker.pubkey()).await, lamports + rent);
+    assert!(!offer.exists(offer.escrow).await);
+    assert!(!offer.exists(offer.vault).await);
+}
+
+#[tokio::test]
+async fn migrate_escrow_grows_an_old_escrow() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+    let escrow = offer.ctx.banks_client.get_account(offer.escrow).await.unwrap().unwrap();
+    let state = Escrow::try_deserialize(&mut escrow.data.as_slice()).unwrap();
+
+    // an escrow as the first deployed version wrote it, without any of the fields appended since
+    let mut data = Escrow::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&SEED.to_le_bytes());
+    data.extend_from_slice(offer.maker.pubkey().as_ref());
+    data.extend_from_slice(offer.mint_a.as_ref());
+    data.extend_from_slice(offer.mint_b.as_ref());
+    data.extend_from_slice(&RECEIVE.to_le_bytes());
+    data.push(state.bump);
+    assert_eq!(data.len(), 8 + Escrow::ORIGINAL_SPACE);
+
+    let rent = offer.ctx.banks_client.get_rent().await.unwrap();
+    let old = Account { lamports: rent.minimum_balance(data.len()), data, owner: escrow::ID, executable: false, rent_epoch: 0 };
+    offer.ctx.set_account(&offer.escrow, &old.into());
+    assert_error(offer.transfer().await, ErrorCode::AccountDidNotDeserialize as u32);
+
+    offer.migrate_escrow().await.unwrap();
+    let escrow = offer.ctx.banks_client.get_account(offer.escrow).await.unwrap().unwrap();
+    let migrated = Escrow::try_deserialize(&mut escrow.data.as_slice()).unwrap();
+    assert_eq!(migrated.maker, offer.maker.pubkey());
+    assert_eq!(migrated.receive, RECEIVE);
+    assert_eq!(migrated.bump, state.bump);
+    assert_eq!((migrated.deposit, migrated.filled, migrated.remaining), (DEPOSIT, 0, RECEIVE));
+    assert!(migrated.expires_at.is_none() && migrated.taker.is_none() && migrated.is_swap());
+    assert_escrow_error(offer.migrate_escrow().await, EscrowError::AlreadyMigrated);
+
+    offer.transfer().await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert_eq!(offer.balance(offer.maker_ata_b).await, RECEIVE);
+    assert!(!offer.exists(offer.escrow).await);
+}
+
+#[tokio::test]
+async fn partial_fills_pay_the_protocol_fee() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.set_fee(100).await.unwrap();
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+
+    // 1% of every piece goes to the treasury, the maker gets the rest
+    offer.take_partial(RECEIVE / 2).await.unwrap();
+    let treasury_ata_b = offer.ata(&offer.ctx.payer.pubkey(), &offer.mint_b);
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT / 2);
+    assert_eq!(offer.balance(offer.maker_ata_b).await, RECEIVE / 2 - RECEIVE / 200);
+    assert_eq!(offer.balance(treasury_ata_b).await, RECEIVE / 200);
+
+    offer.take_partial(RECEIVE / 2).await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert_eq!(offer.balance(offer.maker_ata_b).await, RECEIVE - RECEIVE / 100);
+    assert_eq!(offer.balance(treasury_ata_b).await, RECEIVE / 100);
+    assert!(!offer.exists(offer.escrow).await);
+}
+
+#[tokio::test]
+async fn takers_bound_the_terms_the_maker_can_update() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+
+    // the maker raises the price and withdraws half the deposit ahead of the taker
+    offer.update_offer(Some(2 * RECEIVE), 0, DEPOSIT / 2).await.unwrap();
+
+    assert_escrow_error(offer.transfer_within(RECEIVE).await, EscrowError::SlippageExceeded);
+    assert_escrow_error(offer.take_partial_within(RECEIVE / 2, DEPOSIT / 2).await, EscrowError::SlippageExceeded);
+    assert_eq!(offer.balance(offer.taker_ata_b).await, FUNDS);
+
+    // at the new terms a quarter of receive buys a quarter of what is left
+    offer.take_partial_within(RECEIVE / 2, DEPOSIT / 8).await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT / 8);
+
+    offer.transfer_within(3 * RECEIVE / 2).await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT / 2);
+    assert_eq!(offer.balance(offer.maker_ata_b).await, 2 * RECEIVE);
+}
+
+#[tokio::test]
+async fn basket_take_swaps_every_leg() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let (offered, requested) = offer.two_by_two_basket(FUNDS).await;
+    offer.make_basket(&offered, &requested).await.unwrap();
+
+    let basket = offer.basket();
+    let vaults: Vec<Pubkey> = offered.iter().map(|leg| offer.ata(&basket, &leg.mint)).collect();
+    assert_eq!(offer.balance(vaults[0]).await, DEPOSIT);
+    assert_eq!(offer.balance(vaults[1]).await, 2 * DEPOSIT);
+
+    let accounts = offer.take_basket_accounts(&offered, &requested);
+    offer.take_basket(accounts).await.unwrap();
+
+    // the taker gets both vaults, the maker both requested legs, in atas the take created for them
+    let (maker, taker) = (offer.maker.pubkey(), offer.taker.pubkey());
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert_eq!(offer.balance(offer.ata(&taker, &offered[1].mint)).await, 2 * DEPOSIT);
+    assert_eq!(offer.balance(offer.maker_ata_b).await, RECEIVE);
+    assert_eq!(offer.balance(offer.ata(&maker, &requested[1].mint)).await, 2 * RECEIVE);
+    assert_eq!(offer.balance(offer.ata(&taker, &requested[1].mint)).await, FUNDS - 2 * RECEIVE);
+    assert!(!offer.exists(vaults[0]).await && !offer.exists(vaults[1]).await);
+    assert!(!offer.exists(basket).await);
+}
+
+#[tokio::test]
+async fn basket_take_rejects_accounts_out_of_leg_order() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let (offered, requested) = offer.two_by_two_basket(FUNDS).await;
+    offer.make_basket(&offered, &requested).await.unwrap();
+
+    // the two requested legs swapped, each chunk is complete but sits at the other leg's place
+    let mut accounts = offer.take_basket_accounts(&offered, &requested);
+    accounts[..8].rotate_left(4);
+    assert_escrow_error(offer.take_basket(accounts).await, EscrowError::BasketAccountMismatch);
+
+    // an offered leg read with a mint the basket does not hold
+    let mut accounts = offer.take_basket_accounts(&offered, &requested);
+    accounts[8].pubkey = offer.mint_b;
+    assert_escrow_error(offer.take_basket(accounts).await, EscrowError::BasketAccountMismatch);
+
+    // an offered leg left out
+    let mut accounts = offer.take_basket_accounts(&offered, &requested);
+    accounts.truncate(accounts.len() - 3);
+    assert_escrow_error(offer.take_basket(accounts).await, EscrowError::BasketAccountMismatch);
+
+    assert!(offer.exists(offer.basket()).await);
+    assert_eq!(offer.balance(offer.taker_ata_b).await, FUNDS);
+}
+
+#[tokio::test]
+async fn basket_take_needs_every_requested_leg_covered() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let (offered, requested) = offer.two_by_two_basket(2 * RECEIVE - 1).await;
+    offer.make_basket(&offered, &requested).await.unwrap();
+
+    // the first leg is covered, the second is one short, so nothing moves
+    let accounts = offer.take_basket_accounts(&offered, &requested);
+    assert_escrow_error(offer.take_basket(accounts).await, EscrowError::InsufficientFunds);
+
+    assert_eq!(offer.balance(offer.taker_ata_b).await, FUNDS);
+    assert_eq!(offer.balance(offer.maker_ata_b).await, 0);
+    assert_eq!(offer.balance(offer.ata(&offer.basket(), &offer.mint_a)).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn basket_refund_returns_every_leg() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let (offered, requested) = offer.two_by_two_basket(FUNDS).await;
+    let lamports = offer.lamports(offer.maker.pubkey()).await;
+    offer.make_basket(&offered, &requested).await.unwrap();
+
+    offer.refund_basket(&offered).await.unwrap();
+
+    let (maker, basket) = (offer.maker.pubkey(), offer.basket());
+    assert_eq!(offer.balance(offer.maker_ata_a).await, FUNDS);
+    assert_eq!(offer.balance(offer.ata(&maker, &offered[1].mint)).await, FUNDS);
+    assert_eq!(offer.lamports(maker).await, lamports);
+    assert!(!offer.exists(offer.ata(&basket, &offered[0].mint)).await);
+    assert!(!offer.exists(offer.ata(&basket, &offered[1].mint)).await);
+    assert!(!offer.exists(basket).await);
+}
+
+#[tokio::test]
+async fn basket_take_pays_the_protocol_fee_on_every_requested_leg() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.set_fee(100).await.unwrap();
+
+    let mint_d = offer.create_mint().await;
+    let taker = offer.taker.pubkey();
+    offer.create_ata(&taker, mint_d, FUNDS).await;
+
+    let offered = [BasketLeg { mint: offer.mint_a, amount: DEPOSIT }];
+    let requested = [BasketLeg { mint: offer.mint_b, amount: RECEIVE }, BasketLeg { mint: mint_d, amount: DEPOSIT }];
+    offer.make_basket(&offered, &requested).await.unwrap();
+
+    let accounts = offer.take_basket_accounts(&offered, &requested);
+    offer.take_basket(accounts).await.unwrap();
+
+    let (maker, treasury) = (offer.maker.pubkey(), offer.ctx.payer.pubkey());
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert_eq!(offer.balance(offer.maker_ata_b).await, RECEIVE - RECEIVE / 100);
+    assert_eq!(offer.balance(offer.ata(&treasury, &offer.mint_b)).await, RECEIVE / 100);
+    assert_eq!(offer.balance(offer.ata(&maker, &mint_d)).await, DEPOSIT - DEPOSIT / 100);
+    assert_eq!(offer.balance(offer.ata(&treasury, &mint_d)).await, DEPOSIT / 100);
+    assert_eq!(offer.balance(offer.ata(&taker, &mint_d)).await, FUNDS - DEPOSIT);
+    assert!(!offer.exists(offer.basket()).await);
+}
+
+#[tokio::test]
+async fn maker_accepts_a_counter_offer() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+
+    // the taker escrows a lower price for the whole vault
+    offer.counter_offer(RECEIVE / 2).await.unwrap();
+    let counter_vault = offer.ata(&offer.counter(), &offer.mint_b);
+    assert_eq!(offer.balance(counter_vault).await, RECEIVE / 2);
+    assert_eq!(offer.balance(offer.taker_ata_b).await, FUNDS - RECEIVE / 2);
+
+    offer.accept_counter().await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert_eq!(offer.balance(offer.maker_ata_b).await, RECEIVE / 2);
+    assert!(!offer.exists(offer.counter()).await && !offer.exists(counter_vault).await);
+    assert!(!offer.exists(offer.escrow).await && !offer.exists(offer.vault).await);
+}
+
+#[tokio::test]
+async fn counter_offer_is_void_once_the_vault_changes() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+    offer.counter_offer(RECEIVE / 2).await.unwrap();
+
+    // a partial fill in between leaves less in the vault than the counter was made for
+    offer.take_partial(RECEIVE / 4).await.unwrap();
+    assert_escrow_error(offer.accept_counter().await, EscrowError::CounterOutdated);
+    assert!(offer.exists(offer.counter()).await);
+
+    // the taker backs out and gets the escrowed mint_b and the rent back
+    let lamports = offer.lamports(offer.taker.pubkey()).await;
+    let counter_vault = offer.ata(&offer.counter(), &offer.mint_b);
+    let rent = offer.lamports(offer.counter()).await + offer.lamports(counter_vault).await;
+    offer.withdraw_counter().await.unwrap();
+
+    assert_eq!(offer.balance(offer.taker_ata_b).await, FUNDS - RECEIVE / 4);
+    assert_eq!(offer.lamports(offer.taker.pubkey()).await, lamports + rent);
+    assert!(!offer.exists(offer.counter()).await && !offer.exists(counter_vault).await);
+    assert_eq!(offer.balance(offer.vault).await, DEPOSIT - DEPOSIT / 4);
+}
+
+#[tokio::test]
+async fn payment_escrows_take_no_counter_offers() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.set_time(1_000).await;
+    offer.make_htlc(DEPOSIT, hash(&[7; 32]).to_bytes(), 2_000).await.unwrap();
+
+    assert_escrow_error(offer.counter_offer(RECEIVE).await, EscrowError::PaymentEscrow);
+    assert_eq!(offer.balance(offer.taker_ata_b).await, FUNDS);
+}
+
+#[tokio::test]
+async fn maker_releases_an_arbitrated_escrow() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make_arbitrated(DEPOSIT, Keypair::new().pubkey()).await.unwrap();
+
+    // a payment escrow is neither taken nor refunded, only released or ruled on
+    assert_escrow_error(offer.transfer().await, EscrowError::PaymentEscrow);
+
+    offer.release().await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert_eq!(offer.balance(offer.maker_ata_a).await, FUNDS - DEPOSIT);
+    assert!(!offer.exists(offer.escrow).await && !offer.exists(offer.vault).await);
+}
+
+#[tokio::test]
+async fn only_the_arbiter_rules_on_a_raised_dispute() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let arbiter = Keypair::new();
+    offer.airdrop(&arbiter.pubkey(), 1_000_000_000).await;
+    offer.make_arbitrated(DEPOSIT, arbiter.pubkey()).await.unwrap();
+
+    // nothing to rule on before a party raises a dispute
+    assert_escrow_error(offer.resolve_dispute(&arbiter, 5_000).await, EscrowError::InvalidStatus);
+
+    let taker = offer.taker.insecure_clone();
+    offer.raise_dispute(&taker).await.unwrap();
+
+    let stranger = Keypair::new();
+    offer.airdrop(&stranger.pubkey(), 1_000_000_000).await;
+    assert_escrow_error(offer.resolve_dispute(&stranger, 5_000).await, EscrowError::UnauthorizedArbiter);
+    let maker = offer.maker.insecure_clone();
+    assert_escrow_error(offer.resolve_dispute(&maker, 0).await, EscrowError::UnauthorizedArbiter);
+
+    assert_escrow_error(offer.resolve_dispute(&arbiter, 10_001).await, EscrowError::InvalidSplit);
+    assert_eq!(offer.balance(offer.vault).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn resolved_dispute_splits_the_vault() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let arbiter = Keypair::new();
+    offer.airdrop(&arbiter.pubkey(), 1_000_000_000).await;
+    offer.make_arbitrated(DEPOSIT, arbiter.pubkey()).await.unwrap();
+
+    let maker = offer.maker.insecure_clone();
+    offer.raise_dispute(&maker).await.unwrap();
+    let lamports = offer.lamports(maker.pubkey()).await;
+    let rent = offer.lamports(offer.escrow).await + offer.lamports(offer.vault).await;
+
+    // a quarter to the counterparty, the rest and the rent back to the maker
+    offer.resolve_dispute(&arbiter, 2_500).await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT / 4);
+    assert_eq!(offer.balance(offer.maker_ata_a).await, FUNDS - DEPOSIT / 4);
+    assert_eq!(offer.lamports(maker.pubkey()).await, lamports + rent);
+    assert!(!offer.exists(offer.escrow).await && !offer.exists(offer.vault).await);
+}
+
+#[tokio::test]
+async fn milestones_pay_out_as_they_are_approved() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make_milestones(&[DEPOSIT / 4, 3 * DEPOSIT / 4], 0).await.unwrap();
+    assert_eq!(offer.balance(offer.vault).await, DEPOSIT);
+
+    // milestones can be approved in any order, each one only once
+    offer.approve_milestone(1).await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, 3 * DEPOSIT / 4);
+    assert_escrow_error(offer.approve_milestone(1).await, EscrowError::MilestoneApproved);
+    assert_escrow_error(offer.approve_milestone(2).await, EscrowError::InvalidMilestone);
+
+    // the last approval sweeps what was sent to the vault on top and closes it
+    let payer = offer.ctx.payer.pubkey();
+    let mint_to = spl_token_2022::instruction::mint_to(&offer.token_program, &offer.mint_a, &offer.vault, &payer, &[], 5).unwrap();
+    offer.send(mint_to, &[]).await.unwrap();
+
+    offer.approve_milestone(0).await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT + 5);
+    assert!(!offer.exists(offer.escrow).await && !offer.exists(offer.vault).await);
+}
+
+#[tokio::test]
+async fn cancel_remaining_refunds_after_the_notice_period() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.set_time(1_000).await;
+    offer.make_milestones(&[DEPOSIT / 4, 3 * DEPOSIT / 4], 100).await.unwrap();
+    offer.approve_milestone(0).await.unwrap();
+
+    // the first call only gives notice, the refund waits until notice_period has passed
+    offer.cancel_remaining().await.unwrap();
+    assert_escrow_error(offer.cancel_remaining().await, EscrowError::NoticePeriodActive);
+    offer.set_time(1_099).await;
+    assert_escrow_error(offer.cancel_remaining().await, EscrowError::NoticePeriodActive);
+    assert_eq!(offer.balance(offer.vault).await, 3 * DEPOSIT / 4);
+
+    offer.set_time(1_100).await;
+    offer.cancel_remaining().await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT / 4);
+    assert_eq!(offer.balance(offer.maker_ata_a).await, FUNDS - DEPOSIT / 4);
+    assert!(!offer.exists(offer.escrow).await && !offer.exists(offer.vault).await);
+}
+
+#[tokio::test]
+async fn private_offer_is_only_filled_by_its_taker() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let designated = offer.taker.insecure_clone();
+
+    // the stranger holds enough mint_b and signs as the taker
+    let stranger = Keypair::new();
+    offer.airdrop(&stranger.pubkey(), 1_000_000_000).await;
+    offer.taker = stranger.insecure_clone();
+    offer.taker_ata_a = offer.create_ata(&stranger.pubkey(), offer.mint_a, 0).await;
+    offer.taker_ata_b = offer.create_ata(&stranger.pubkey(), offer.mint_b, FUNDS).await;
+
+    offer.make_for(DEPOSIT, RECEIVE, None, 0, Some(designated.pubkey())).await.unwrap();
+    assert_escrow_error(offer.transfer().await, EscrowError::UnauthorizedTaker);
+    assert_escrow_error(offer.take_partial(RECEIVE / 2).await, EscrowError::UnauthorizedTaker);
+    assert_eq!(offer.balance(offer.taker_ata_b).await, FUNDS);
+
+    offer.taker = designated;
+    offer.taker_ata_a = offer.ata(&offer.taker.pubkey(), &offer.mint_a);
+    offer.taker_ata_b = offer.ata(&offer.taker.pubkey(), &offer.mint_b);
+    offer.transfer().await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn book_follows_fills_updates_and_refunds() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.open_book_page().await.unwrap();
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+
+    let offers = offer.open_offers().await;
+    assert_eq!(offers.len(), 1);
+    assert_eq!(offers[0].escrow, offer.escrow);
+    assert_eq!(offers[0].price, 2 * escrow::PRICE_SCALE);
+
+    // a partial fill keeps the offer listed, a new price is written to the book
+    offer.take_partial(RECEIVE / 2).await.unwrap();
+    offer.update_offer(Some(RECEIVE), 0, 0).await.unwrap();
+    assert_eq!(offer.open_offers().await[0].price, 4 * escrow::PRICE_SCALE);
+
+    // filling the rest closes the escrow and takes it off the book
+    offer.take_partial(RECEIVE).await.unwrap();
+    assert!(!offer.exists(offer.escrow).await);
+    assert!(offer.open_offers().await.is_empty());
+
+    // a listed escrow can not be refunded without its page
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+    offer.book = None;
+    let maker = offer.maker.insecure_clone();
+    assert_escrow_error(offer.refund(&maker).await, EscrowError::BookAccountMismatch);
+
+    // nor with page 0 of another pair, passed together with that pair's mint_b
+    let (mint_b, mint_c) = (offer.mint_b, offer.create_mint().await);
+    offer.mint_b = mint_c;
+    offer.open_book_page().await.unwrap();
+    assert_error(offer.refund(&maker).await, ErrorCode::ConstraintSeeds as u32);
+    offer.mint_b = mint_b;
+
+    offer.book = Some(escrow::client::book_address(&offer.mint_a, &offer.mint_b, 0));
+    offer.refund(&maker).await.unwrap();
+    assert!(offer.open_offers().await.is_empty());
+}
+
+// 2.5 mint_b per mint_a, with 6 decimals on both sides and in the feed
+fn feed(publish_time: i64) -> PriceFeed {
+    PriceFeed { price: 2_500_000, conf: 0, expo: -6, publish_time, trading: true }
+}
+
+#[tokio::test]
+async fn oracle_offer_is_priced_at_a_fresh_feed() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let oracle = Pubkey::new_unique();
+    offer.set_time(1_000).await;
+    offer.set_price_feed(oracle, feed(990));
+    offer.make_oracle(1_000_001, oracle, 100, Some(60), None).await.unwrap();
+
+    // 1.000001 a at 2.5 plus 1% is 2.525002525 b, rounded up for the maker
+    offer.price_feed = Some(oracle);
+    offer.transfer().await.unwrap();
+    assert_eq!(offer.balance(offer.maker_ata_b).await, 2_525_003);
+    assert_eq!(offer.balance(offer.taker_ata_b).await, FUNDS - 2_525_003);
+    assert_eq!(offer.balance(offer.taker_ata_a).await, 1_000_001);
+}
+
+#[tokio::test]
+async fn oracle_offer_rejects_unusable_feeds() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let oracle = Pubkey::new_unique();
+    offer.set_time(1_000).await;
+    offer.set_price_feed(oracle, feed(990));
+    offer.make_oracle(DEPOSIT, oracle, 0, Some(60), Some(100)).await.unwrap();
+    offer.price_feed = Some(oracle);
+
+    // published more than max_price_age ago
+    offer.set_time(1_051).await;
+    assert_escrow_error(offer.transfer().await, EscrowError::StalePrice);
+
+    let halted = PriceFeed { trading: false, ..feed(1_050) };
+    offer.set_price_feed(oracle, halted);
+    assert_escrow_error(offer.transfer().await, EscrowError::InvalidPriceFeed);
+
+    let negative = PriceFeed { price: -1, ..feed(1_050) };
+    offer.set_price_feed(oracle, negative);
+    assert_escrow_error(offer.transfer().await, EscrowError::InvalidPriceFeed);
+
+    // a confidence interval just over the maker's 1% of the price
+    let uncertain = PriceFeed { conf: 25_001, ..feed(1_050) };
+    offer.set_price_feed(oracle, uncertain);
+    assert_escrow_error(offer.transfer().await, EscrowError::PriceTooUncertain);
+
+    // a fresh feed only helps if it is the one the maker picked
+    let other = Pubkey::new_unique();
+    offer.set_price_feed(other, feed(1_050));
+    offer.price_feed = Some(other);
+    assert_escrow_error(offer.transfer().await, EscrowError::PriceFeedMismatch);
+
+    // 1% exactly is still accepted
+    offer.set_price_feed(oracle, PriceFeed { conf: 25_000, ..feed(1_050) });
+    offer.price_feed = Some(oracle);
+    offer.transfer().await.unwrap();
+    assert_eq!(offer.balance(offer.maker_ata_b).await, 2 * DEPOSIT + DEPOSIT / 2);
+}
+
+#[tokio::test]
+async fn collection_offer_takes_a_verified_nft() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make_collection(DEPOSIT).await.unwrap();
+
+    // mint_b is the collection, the taker pays with any nft verified in it
+    let nft = offer.create_nft(offer.mint_b, true).await;
+    offer.nft = Some(nft);
+    offer.transfer().await.unwrap();
+
+    assert_eq!(offer.balance(offer.ata(&offer.maker.pubkey(), &nft)).await, 1);
+    assert_eq!(offer.balance(offer.ata(&offer.taker.pubkey(), &nft)).await, 0);
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert!(!offer.exists(offer.escrow).await);
+}
+
+#[tokio::test]
+async fn collection_offer_rejects_unverified_and_foreign_nfts() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make_collection(DEPOSIT).await.unwrap();
+
+    // anyone can claim a collection in the metadata, only a verified one counts
+    let unverified = offer.create_nft(offer.mint_b, false).await;
+    offer.nft = Some(unverified);
+    assert_escrow_error(offer.transfer().await, EscrowError::CollectionMismatch);
+
+    let other_collection = offer.create_mint().await;
+    let foreign = offer.create_nft(other_collection, true).await;
+    offer.nft = Some(foreign);
+    assert_escrow_error(offer.transfer().await, EscrowError::CollectionMismatch);
+
+    assert_eq!(offer.balance(offer.vault).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn token_2022_mints_go_through_the_token_interface() {
+    let mut offer = Offer::new(spl_token_2022::ID, FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+    assert_eq!(offer.balance(offer.vault).await, DEPOSIT);
+
+    offer.transfer().await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert_eq!(offer.balance(offer.maker_ata_b).await, RECEIVE);
+    assert!(!offer.exists(offer.escrow).await);
+}
+
+#[tokio::test]
+async fn taker_without_enough_mint_b_can_not_take() {
+    let mut offer = Offer::new(spl_token::ID, RECEIVE - 1).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+
+    let result = offer.transfer().await;
+    assert_escrow_error(result, EscrowError::InsufficientFunds);
+}
+
+#[tokio::test]
+async fn empty_vault_can_not_be_taken() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+
+    // the program never leaves a live vault empty, so the balance is zeroed in the bank directly
+    let mut vault = offer.ctx.banks_client.get_account(offer.vault).await.unwrap().unwrap();
+    let mut state = spl_token::state::Account::unpack(&vault.data).unwrap();
+    state.amount = 0;
+    spl_token::state::Account::pack(state, &mut vault.data).unwrap();
+    offer.ctx.set_account(&offer.vault, &vault.into());
+
+    let result = offer.transfer().await;
+    assert_escrow_error(result, EscrowError::EmptyVault);
+}
+
+#[tokio::test]
+async fn expired_native_offer_returns_the_deposit_to_the_maker() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.mint_a = spl_token::native_mint::ID;
+    let lamports = offer.lamports(offer.maker.pubkey()).await;
+
+    offer.set_time(1_000).await;
+    offer.make_expiring(DEPOSIT, RECEIVE, Some(2_000), 5_000).await.unwrap();
+    // the escrow holds the deposit on top of its rent
+    let held = offer.lamports(offer.escrow).await;
+    assert!(held > DEPOSIT);
+    assert_eq!(offer.lamports(offer.maker.pubkey()).await, lamports - held);
+
+    let caller = Keypair::new();
+    offer.airdrop(&caller.pubkey(), 1_000_000_000).await;
+    assert_escrow_error(offer.expire(&caller).await, EscrowError::NotExpired);
+
+    // the deposit and the rent go back to the maker, except for the bounty
+    offer.set_time(2_000).await;
+    offer.expire(&caller).await.unwrap();
+    assert_eq!(offer.lamports(offer.maker.pubkey()).await, lamports - 5_000);
+    assert_eq!(offer.lamports(caller.pubkey()).await, 1_000_000_000 + 5_000);
+    assert!(!offer.exists(offer.escrow).await);
+}
+
+#[tokio::test]
+async fn only_the_maker_can_refund() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+
+    // the stranger signs with a token account of their own, so the escrow's maker constraints are what stop them
+    // the escrow address is derived from the maker, and anchor checks those seeds before has_one
+    let stranger = Keypair::new();
+    offer.create_ata(&stranger.pubkey(), offer.mint_a, 0).await;
+
+    let result = offer.refund(&stranger).await;
+    assert_error(result, ErrorCode::ConstraintSeeds as u32);
+
+    // a copy of the escrow at the stranger's own address passes the seeds, so has_one is what catches the wrong maker
+    let (forged, bump) = Pubkey::find_program_address(&[b"escrow", stranger.pubkey().as_ref(), SEED.to_le_bytes().as_ref()], &escrow::ID);
+    let mut account = offer.ctx.banks_client.get_account(offer.escrow).await.unwrap().unwrap();
+    let mut state = Escrow::try_deserialize(&mut account.data.as_slice()).unwrap();
+    state.bump = bump;
+    account.data.clear();
+    state.try_serialize(&mut account.data).unwrap();
+    offer.ctx.set_account(&forged, &account.into());
+
+    let escrow = offer.escrow;
+    offer.escrow = forged;
+    let result = offer.refund(&stranger).await;
+    assert_error(result, ErrorCode::ConstraintHasOne as u32);
+    offer.escrow = escrow;
+
+    assert_eq!(offer.balance(offer.vault).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn offer_can_only_be_taken_once() {
+    let mut offer = Offer::new(spl_token::ID, 2 * FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+    offer.transfer().await.unwrap();
+
+    let result = offer.transfer().await;
+    assert_error(result, ErrorCode::AccountNotInitialized as u32);
+
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert_eq!(offer.balance(offer.taker_ata_b).await, 2 * FUNDS - RECEIVE);
+}
+
+#[tokio::test]
+async fn stream_vests_linearly_and_cancel_splits_the_vault() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.set_time(1_000).await;
+    offer.make_stream(DEPOSIT, 1_000, 2_000).await.unwrap();
+
+    let result = offer.withdraw_streamed().await;
+    assert_escrow_error(result, EscrowError::NothingVested);
+
+    // a quarter of the way through
+    offer.set_time(1_250).await;
+    offer.withdraw_streamed().await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT / 4);
+
+    // halfway the recipient has a quarter left to claim, the other half goes back to the maker
+    offer.set_time(1_500).await;
+    offer.cancel_stream().await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT / 2);
+    assert_eq!(offer.balance(offer.maker_ata_a).await, FUNDS - DEPOSIT / 2);
+    assert!(!offer.exists(offer.escrow).await);
+}
+
+#[tokio::test]
+async fn stream_closes_once_fully_withdrawn() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.set_time(1_000).await;
+    offer.make_stream(DEPOSIT, 1_000, 2_000).await.unwrap();
+
+    offer.set_time(5_000).await;
+    offer.withdraw_streamed().await.unwrap();
+
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert!(!offer.exists(offer.escrow).await);
+    assert!(!offer.exists(offer.vault).await);
+}
+
+#[tokio::test]
+async fn final_stream_claim_sweeps_tokens_sent_to_the_vault() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.set_time(1_000).await;
+    offer.make_stream(DEPOSIT, 1_000, 2_000).await.unwrap();
+
+    // anyone can send tokens to the vault, they must not keep it from being closed
+    let payer = offer.ctx.payer.pubkey();
+    let mint_to = spl_token_2022::instruction::mint_to(&offer.token_program, &offer.mint_a, &offer.vault, &payer, &[], 7).unwrap();
+    offer.send(mint_to, &[]).await.unwrap();
+
+    offer.set_time(1_500).await;
+    offer.withdraw_streamed().await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT / 2);
+
+    offer.set_time(2_000).await;
+    offer.withdraw_streamed().await.unwrap();
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT + 7);
+    assert!(!offer.exists(offer.escrow).await);
+    assert!(!offer.exists(offer.vault).await);
+}
+
+#[tokio::test]
+async fn ring_settles_without_a_taker() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+    let reverse = offer.make_reverse(RECEIVE, DEPOSIT).await.unwrap();
+
+    let (maker, taker) = (offer.maker.pubkey(), offer.taker.pubkey());
+    let ring = [(offer.escrow, maker, offer.mint_a, offer.mint_b), (reverse, taker, offer.mint_b, offer.mint_a)];
+    offer.settle_ring(&ring).await.unwrap();
+
+    assert_eq!(offer.balance(offer.maker_ata_b).await, RECEIVE);
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert!(!offer.exists(offer.escrow).await);
+    assert!(!offer.exists(reverse).await);
+}
+
+#[tokio::test]
+async fn ring_pays_the_protocol_fee_on_every_leg() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.set_fee(100).await.unwrap();
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+    let reverse = offer.make_reverse(RECEIVE, DEPOSIT).await.unwrap();
+
+    let (maker, taker, treasury) = (offer.maker.pubkey(), offer.taker.pubkey(), offer.ctx.payer.pubkey());
+    let ring = [(offer.escrow, maker, offer.mint_a, offer.mint_b), (reverse, taker, offer.mint_b, offer.mint_a)];
+    offer.settle_ring(&ring).await.unwrap();
+
+    // each maker pays 1% of the vault they get, in its mint
+    assert_eq!(offer.balance(offer.maker_ata_b).await, RECEIVE - RECEIVE / 100);
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT - DEPOSIT / 100);
+    assert_eq!(offer.balance(offer.ata(&treasury, &offer.mint_b)).await, RECEIVE / 100);
+    assert_eq!(offer.balance(offer.ata(&treasury, &offer.mint_a)).await, DEPOSIT / 100);
+}
+
+#[tokio::test]
+async fn ring_needs_every_offer_covered() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    offer.make(DEPOSIT, RECEIVE).await.unwrap();
+    let reverse = offer.make_reverse(RECEIVE - 1, DEPOSIT).await.unwrap();
+
+    let (maker, taker) = (offer.maker.pubkey(), offer.taker.pubkey());
+    let ring = [(offer.escrow, maker, offer.mint_a, offer.mint_b), (reverse, taker, offer.mint_b, offer.mint_a)];
+    let result = offer.settle_ring(&ring).await;
+    assert_escrow_error(result, EscrowError::InsufficientFunds);
+
+    // a ring of one escrow is rejected
+    let result = offer.settle_ring(&ring[..1]).await;
+    assert_escrow_error(result, EscrowError::InvalidRing);
+}
+
+#[tokio::test]
+async fn htlc_pays_out_for_the_secret_and_reveals_it() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let secret = [7u8; 32];
+    let lock = hash(&secret).to_bytes();
+
+    offer.set_time(1_000).await;
+    offer.make_htlc(DEPOSIT, lock, 2_000).await.unwrap();
+
+    let result = offer.claim_htlc([8u8; 32]).await;
+    assert_escrow_error(result, EscrowError::InvalidSecret);
+
+    // the maker can not take it back before the timeout
+    let result = offer.reclaim_htlc().await;
+    assert_escrow_error(result, EscrowError::NotExpired);
+
+    // the native processor does not record sol_log_data, so the HtlcClaimed event is not visible here
+    offer.claim_htlc(secret).await.unwrap();
+
+    assert_eq!(offer.balance(offer.taker_ata_a).await, DEPOSIT);
+    assert!(!offer.exists(offer.escrow).await);
+}
+
+#[tokio::test]
+async fn htlc_returns_to_the_maker_after_the_timeout() {
+    let mut offer = Offer::new(spl_token::ID, FUNDS).await;
+    let secret = [7u8; 32];
+
+    offer.set_time(1_000).await;
+    offer.make_htlc(DEPOSIT, hash(&secret).to_bytes(), 2_000).await.unwrap();
+
+    offer.set_time(2_000).await;
+    let result = offer.claim_htlc(secret).await;
+    assert_escrow_error(result, EscrowError::Expired);
+
+    offer.reclaim_htlc().await.unwrap();
+    assert_eq!(offer.balance(offer.maker_ata_a).await, FUNDS);
+    assert!(!offer.exists(offer.escrow).await);
+}
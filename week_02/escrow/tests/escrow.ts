@@ -134,12 +134,23 @@ describe("escrow", () => {
       escrowPda,
       true
     );
+
+    // the protocol config is global, fees stay at zero so the amounts below are exact
+    // only the upgrade authority, the wallet that deployed the program, can create it
+    const [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    await program.methods
+      .initializeConfig(0, wallet.publicKey)
+      .accountsPartial({ admin: wallet.publicKey, programData })
+      .rpc();
   });
 
   describe("make", () => {
     it("Creates an escrow successfully", async () => {
       const tx = await program.methods
-        .make(seed, depositAmount, receiveAmount)
+        .make(seed, depositAmount, receiveAmount, null, new anchor.BN(0), null)
         .accountsPartial({
           maker: maker.publicKey,
           mintA: mintA,
@@ -197,7 +208,7 @@ describe("escrow", () => {
 
       try {
         await program.methods
-          .make(invalidSeed, invalidAmount, receiveAmount)
+          .make(invalidSeed, invalidAmount, receiveAmount, null, new anchor.BN(0), null)
           .accountsPartial({
             maker: maker.publicKey,
             mintA: mintA,
@@ -240,7 +251,7 @@ describe("escrow", () => {
 
       try {
         await program.methods
-          .make(invalidSeed, excessiveAmount, receiveAmount)
+          .make(invalidSeed, excessiveAmount, receiveAmount, null, new anchor.BN(0), null)
           .accountsPartial({
             maker: maker.publicKey,
             mintA: mintA,
@@ -266,12 +277,13 @@ describe("escrow", () => {
   describe("transfer", () => {
     it("Completes the escrow transfer successfully", async () => {
       const tx = await program.methods
-        .transfer(seed)
+        .transfer(seed, receiveAmount)
         .accountsPartial({
           maker: maker.publicKey,
           taker: taker.publicKey,
           mintA: mintA,
           mintB: mintB,
+          treasury: wallet.publicKey,
           makerAtaB: makerAtaB,
           takerAtaA: takerAtaA,
           takerAtaB: takerAtaB,
@@ -341,7 +353,7 @@ describe("escrow", () => {
 
       // Create escrow with deposit
       await program.methods
-        .make(emptyVaultSeed, depositAmount, receiveAmount)
+        .make(emptyVaultSeed, depositAmount, receiveAmount, null, new anchor.BN(0), null)
         .accountsPartial({
           maker: maker.publicKey,
           mintA: mintA,
@@ -358,12 +370,13 @@ describe("escrow", () => {
 
       // Complete the transfer once
       await program.methods
-        .transfer(emptyVaultSeed)
+        .transfer(emptyVaultSeed, receiveAmount)
         .accountsPartial({
           maker: maker.publicKey,
           taker: taker.publicKey,
           mintA: mintA,
           mintB: mintB,
+          treasury: wallet.publicKey,
           makerAtaB: makerAtaB,
           takerAtaA: takerAtaA,
           takerAtaB: takerAtaB,
@@ -379,12 +392,13 @@ describe("escrow", () => {
       // Now the escrow should be closed, so any attempt to use it should fail
       try {
         await program.methods
-          .transfer(emptyVaultSeed)
+          .transfer(emptyVaultSeed, receiveAmount)
           .accountsPartial({
             maker: maker.publicKey,
             taker: taker.publicKey,
             mintA: mintA,
             mintB: mintB,
+            treasury: wallet.publicKey,
             makerAtaB: makerAtaB,
             takerAtaA: takerAtaA,
             takerAtaB: takerAtaB,
@@ -430,7 +444,7 @@ describe("escrow", () => {
 
       // Create escrow
       await program.methods
-        .make(refundSeed, depositAmount, receiveAmount)
+        .make(refundSeed, depositAmount, receiveAmount, null, new anchor.BN(0), null)
         .accountsPartial({
           maker: maker.publicKey,
           mintA: mintA,
@@ -513,7 +527,7 @@ describe("escrow", () => {
 
       // Create escrow
       await program.methods
-        .make(unauthorizedSeed, depositAmount, receiveAmount)
+        .make(unauthorizedSeed, depositAmount, receiveAmount, null, new anchor.BN(0), null)
         .accountsPartial({
           maker: maker.publicKey,
           mintA: mintA,